@@ -11,11 +11,13 @@ int test_u64() {
     const uint8_t expected[8] = {0, 0, 0, 0, 5, 0, 0, 0};
     uint8_t buffer[8];
     memcpy(buffer, expected, 8);
-    const SECDED_64 secded = SECDED_64_new(57);
-    SECDED_64_encode(&secded, buffer);
+    SECDED_64 *secded = SECDED_64_new(57);
+    SECDED_64_encode(secded, buffer, 8);
     buffer[7] ^= 1u << 1u;
-    if (!SECDED_64_decode(&secded, buffer)) {
-        printf("TESTING U64 -- FAILED: DECODE FAILED\n");
+    SECDED_STATUS status = SECDED_64_decode(secded, buffer, 8);
+    SECDED_64_free(secded);
+    if (status != SECDED_CORRECTED) {
+        printf("TESTING U64 -- FAILED: DECODE FAILED: %d\n", status);
         return 1;
     }
     for (int i = 0; i < 8; i++) {
@@ -33,14 +35,16 @@ int test_u128() {
     const uint8_t expected[16] = {0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0};
     uint8_t buffer[16];
     memcpy(buffer, expected, 16);
-    const SECDED_128 secded = SECDED_128_new(57);
-    SECDED_128_encode(&secded, buffer);
+    SECDED_128 *secded = SECDED_128_new(57);
+    SECDED_128_encode(secded, buffer, 16);
     {  // Borrow `buffer`'s active part for test modification
         uint8_t *active_buffer = &buffer[8];
         active_buffer[7] ^= 1u << 1u;
     }
-    if (!SECDED_128_decode(&secded, buffer)) {
-        printf("TESTING U128 -- FAILED: DECODE FAILED\n");
+    SECDED_STATUS status = SECDED_128_decode(secded, buffer, 16);
+    SECDED_128_free(secded);
+    if (status != SECDED_CORRECTED) {
+        printf("TESTING U128 -- FAILED: DECODE FAILED: %d\n", status);
         return 2;
     }
     for (int i = 0; i < 16; i++) {
@@ -53,17 +57,43 @@ int test_u128() {
     return 0;
 }
 
+int test_blocks() {
+    printf("TESTING BLOCKS: \r\n");
+    const char *message = "Single Error Correction, Double Error Detection";
+    const size_t length = strlen(message);
+    SECDED_64 *secded = SECDED_64_new(57);
+    const size_t size = SECDED_64_encoded_size(secded, length);
+    uint8_t blocks[64];
+    char decoded[64] = {0};
+    SECDED_64_encode_blocks(secded, (const uint8_t *)message, length, blocks, size);
+    blocks[10] ^= 1u << 4u;
+    SECDED_CORRECTION correction;
+    uint8_t block[8];
+    memcpy(block, &blocks[8], 8);
+    SECDED_64_decode_with_correction(secded, block, 8, &correction);
+    SECDED_STATUS status = SECDED_64_decode_blocks(secded, blocks, size, (uint8_t *)decoded, length);
+    SECDED_64_free(secded);
+    if (status != SECDED_CORRECTED || correction.bit != 44 || strcmp(message, decoded) != 0) {
+        printf("TESTING BLOCKS -- FAILED: %d, bit %u, %s\n", status, correction.bit, decoded);
+        return 8;
+    }
+    printf("TESTING BLOCKS -- OK\r\n");
+    return 0;
+}
+
 #ifdef SECDED_FEATURES_DYN
 int test_dyn() {
     const uint8_t expected[8] = {0, 0, 0, 0, 5, 0, 0, 0};
     uint8_t buffer[8];
     memcpy(buffer, expected, 8);
     printf("TESTING DYN:\r\n");
-    const SECDED_DYN *secded = SECDED_DYN_new(57);
+    SECDED_DYN *secded = SECDED_DYN_new(57);
     SECDED_DYN_encode(secded, buffer, 8);
     buffer[0] ^= 128u;
-    if (!SECDED_DYN_decode(secded, buffer, 8)) {
-        printf("TESTING DYN -- FAILED: DECODE FAILED\n");
+    SECDED_STATUS status = SECDED_DYN_decode(secded, buffer, 8);
+    SECDED_DYN_free(secded);
+    if (status != SECDED_CORRECTED) {
+        printf("TESTING DYN -- FAILED: DECODE FAILED: %d\n", status);
         return 4;
     }
     for (int i = 0; i < 8; i++) {
@@ -72,7 +102,6 @@ int test_dyn() {
             return 4;
         }
     }
-    SECDED_DYN_free(secded);
     printf("TESTING DYN -- OK\r\n");
     return 0;
 }
@@ -81,8 +110,9 @@ int test_dyn() {
 int main(int argc, char const *argv[]) {
     int status = test_u64();
     status |= test_u128();
+    status |= test_blocks();
 #ifdef SECDED_FEATURES_DYN
     status |= test_dyn();
 #endif
     return status;
-}
\ No newline at end of file
+}
@@ -2,68 +2,206 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+/* The C API of src/ffi.rs: keep both in sync. */
+
 #ifndef SECDED_H
 #define SECDED_H
-#include <stdbool.h>
 #include <stddef.h>
 #include <stdint.h>
 
-typedef struct SECDED_64 {
-    uint8_t encodable_size;
-    uint8_t code_size;
-    uint8_t mask;
-    uint64_t coding_matrix[6];
-    uint16_t syndromes[64];
-} SECDED_64;
+#ifdef __cplusplus
+#define SECDED_STATIC_ASSERT static_assert
+#define SECDED_ALIGNOF alignof
+extern "C" {
+#else
+#define SECDED_STATIC_ASSERT _Static_assert
+#define SECDED_ALIGNOF _Alignof
+#endif
+
+// The outcome of an operation.
+typedef int32_t SECDED_STATUS;
+// No error was found.
+#define SECDED_CLEAN 0
+// A single error was found and corrected.
+#define SECDED_CORRECTED 1
+// At least 2 errors were detected: the data is left untouched.
+#define SECDED_UNCORRECTABLE 2
+// A pointer was NULL, the size didn't match the codec, or the data to encode had bits set outside of its
+// payload: nothing was done.
+#define SECDED_INVALID_ARGUMENT 3
+// The library panicked, and the data may have been partially modified.
+#define SECDED_INTERNAL_ERROR 4
+
+// The outcome of a decoding.
+typedef struct SECDED_CORRECTION {
+    // The codec's syndrome, 0 if no error was found.
+    uint64_t syndrome;
+    // The index of the corrected bit, counted from the right-most bit of the slice, if status is
+    // SECDED_CORRECTED.
+    uint32_t bit;
+    SECDED_STATUS status;
+} SECDED_CORRECTION;
+
+SECDED_STATIC_ASSERT(sizeof(SECDED_STATUS) == 4, "layout mismatch with the Rust library");
+SECDED_STATIC_ASSERT(sizeof(SECDED_CORRECTION) == 16, "layout mismatch with the Rust library");
+SECDED_STATIC_ASSERT(SECDED_ALIGNOF(SECDED_CORRECTION) == SECDED_ALIGNOF(uint64_t), "layout mismatch with the Rust library");
+
+// Returns the reason of the last failure on the calling thread, or NULL if nothing failed yet.
+// The string remains valid until the next failure on the same thread.
+const char *SECDED_last_error(void);
+
+// A SecDed64 codec, working on 8 bytes slices.
+typedef struct SECDED_64 SECDED_64;
+
+// Returns a handle to a SecDed64 codec, or NULL if encodable_size > 57.
+// The handle must be released with SECDED_64_free().
+SECDED_64 *SECDED_64_new(size_t encodable_size);
+
+// Releases secded, which may be NULL.
+void SECDED_64_free(SECDED_64 *secded);
+
+// Returns the number of bits secded encodes, or 0 if it is NULL.
+size_t SECDED_64_encodable_size(const SECDED_64 *secded);
+
+// Returns the number of bits of code secded adds to the payload, or 0 if it is NULL.
+size_t SECDED_64_code_size(const SECDED_64 *secded);
+
+// Returns the size in bytes of the smallest slice secded works on, or 0 if it is NULL.
+size_t SECDED_64_slice_size(const SECDED_64 *secded);
+
+// Encodes data[..size] in place. The code bits must be set to 0.
+SECDED_STATUS SECDED_64_encode(const SECDED_64 *secded, uint8_t *data, size_t size);
+
+// Decodes data[..size] in place, and resets the code bits to 0.
+SECDED_STATUS SECDED_64_decode(const SECDED_64 *secded, uint8_t *data, size_t size);
+
+// Decodes data[..size] in place like SECDED_64_decode(), and describes the outcome in correction,
+// which may be NULL.
+SECDED_STATUS SECDED_64_decode_with_correction(const SECDED_64 *secded, uint8_t *data, size_t size, SECDED_CORRECTION *correction);
+
+// Encodes the count codewords of size bytes stored contiguously in data. Nothing is encoded if any
+// of them has bits set outside of its payload.
+SECDED_STATUS SECDED_64_encode_batch(const SECDED_64 *secded, uint8_t *data, size_t size, size_t count);
+
+// Decodes the count codewords of size bytes stored contiguously in data, and returns the worst
+// status among them. Unless it is NULL, corrections[i] describes the outcome for codeword i.
+SECDED_STATUS SECDED_64_decode_batch(const SECDED_64 *secded, uint8_t *data, size_t size, size_t count, SECDED_CORRECTION *corrections);
+
+// Returns the size in bytes of the blocks SECDED_64_encode_blocks() splits length bytes into, or 0 if
+// secded is NULL, can't carry a byte of payload per block, or if that size overflows.
+size_t SECDED_64_encoded_size(const SECDED_64 *secded, size_t length);
+
+// Splits data[..length] into blocks carrying as many whole bytes of payload as secded can encode,
+// and encodes them into blocks[..size], size being SECDED_64_encoded_size(secded, length).
+SECDED_STATUS SECDED_64_encode_blocks(const SECDED_64 *secded, const uint8_t *data, size_t length, uint8_t *blocks, size_t size);
+
+// Decodes blocks[..size], produced by SECDED_64_encode_blocks(), into data[..length], and returns
+// the worst status among the blocks. The blocks are left untouched.
+SECDED_STATUS SECDED_64_decode_blocks(const SECDED_64 *secded, const uint8_t *blocks, size_t size, uint8_t *data, size_t length);
 
-//  Returns a `SecDed64` Codec, which you can use through `SECDED_64_encode(...)` and `SECDED_64_decode(...)`
-//  Since `SecDed64`'s drop is trivial, the value is returned and no `free` call is required
-SECDED_64 SECDED_64_new(size_t encodable_bits);
+// A SecDed128 codec, working on 16 bytes slices.
+typedef struct SECDED_128 SECDED_128;
 
-//  A wrapper to `secded.encode(&data[..8])`
-void SECDED_64_encode(const SECDED_64 *secded, uint8_t data[8]);
+// Returns a handle to a SecDed128 codec, or NULL if encodable_size > 120.
+// The handle must be released with SECDED_128_free().
+SECDED_128 *SECDED_128_new(size_t encodable_size);
 
-//  A wrapper to `secded.decode(&data[..8])`
-bool SECDED_64_decode(const SECDED_64 *secded, uint8_t data[8]);
+// Releases secded, which may be NULL.
+void SECDED_128_free(SECDED_128 *secded);
 
-typedef struct SECDED_128 {
-    uint8_t encodable_size;
-    uint8_t code_size;
-    uint8_t mask;
-    uint64_t correction_matrix[14];
-    uint16_t syndromes[128];
-} SECDED_128;
+// Returns the number of bits secded encodes, or 0 if it is NULL.
+size_t SECDED_128_encodable_size(const SECDED_128 *secded);
 
-//  Returns a `SecDed128` Codec, which you can use through `SECDED_128_encode(...)` and `SECDED_128_decode(...)`
-//  Since `SecDed128`'s drop is trivial, the value is returned and no `free` call is required
-SECDED_128 SECDED_128_new(size_t encodable_bits);
+// Returns the number of bits of code secded adds to the payload, or 0 if it is NULL.
+size_t SECDED_128_code_size(const SECDED_128 *secded);
 
-//  A wrapper to `secded.encode(&data[..16])`
-void SECDED_128_encode(const SECDED_128 *secded, uint8_t data[16]);
+// Returns the size in bytes of the smallest slice secded works on, or 0 if it is NULL.
+size_t SECDED_128_slice_size(const SECDED_128 *secded);
 
-//  A wrapper to `secded.decode(&data[..16])`
-bool SECDED_128_decode(const SECDED_128 *secded, uint8_t data[16]);
+// Encodes data[..size] in place. The code bits must be set to 0.
+SECDED_STATUS SECDED_128_encode(const SECDED_128 *secded, uint8_t *data, size_t size);
+
+// Decodes data[..size] in place, and resets the code bits to 0.
+SECDED_STATUS SECDED_128_decode(const SECDED_128 *secded, uint8_t *data, size_t size);
+
+// Decodes data[..size] in place like SECDED_128_decode(), and describes the outcome in correction,
+// which may be NULL.
+SECDED_STATUS SECDED_128_decode_with_correction(const SECDED_128 *secded, uint8_t *data, size_t size, SECDED_CORRECTION *correction);
+
+// Encodes the count codewords of size bytes stored contiguously in data. Nothing is encoded if any
+// of them has bits set outside of its payload.
+SECDED_STATUS SECDED_128_encode_batch(const SECDED_128 *secded, uint8_t *data, size_t size, size_t count);
+
+// Decodes the count codewords of size bytes stored contiguously in data, and returns the worst
+// status among them. Unless it is NULL, corrections[i] describes the outcome for codeword i.
+SECDED_STATUS SECDED_128_decode_batch(const SECDED_128 *secded, uint8_t *data, size_t size, size_t count, SECDED_CORRECTION *corrections);
+
+// Returns the size in bytes of the blocks SECDED_128_encode_blocks() splits length bytes into, or 0 if
+// secded is NULL, can't carry a byte of payload per block, or if that size overflows.
+size_t SECDED_128_encoded_size(const SECDED_128 *secded, size_t length);
+
+// Splits data[..length] into blocks carrying as many whole bytes of payload as secded can encode,
+// and encodes them into blocks[..size], size being SECDED_128_encoded_size(secded, length).
+SECDED_STATUS SECDED_128_encode_blocks(const SECDED_128 *secded, const uint8_t *data, size_t length, uint8_t *blocks, size_t size);
+
+// Decodes blocks[..size], produced by SECDED_128_encode_blocks(), into data[..length], and returns
+// the worst status among the blocks. The blocks are left untouched.
+SECDED_STATUS SECDED_128_decode_blocks(const SECDED_128 *secded, const uint8_t *blocks, size_t size, uint8_t *data, size_t length);
 
 #ifdef SECDED_FEATURES_DYN
-//  Since `SecDedDynamic` can't be easily represented in C, and has a specialized `free` operation,
-//  you can only manipulate it as a pointer.
-//  This 0-sized type is only here for type-safety.
-typedef struct SECDED_DYN {
-} SECDED_DYN;
 
-//  Returns a pointer to a `SecDedDynamic` Codec instance, which you can use through `SECDED_DYNAMIC_encode(...)` and
-//  `SECDED_DYNAMIC_decode(...)`.
-//  Dropping SECDED_DYN is non-trivial and should be done using `SECDED_DYN_free()`
-const SECDED_DYN *SECDED_DYN_new(size_t encodable_bits);
+// A SecDedDynamic codec, working on slices of any size that can hold its payload and code.
+typedef struct SECDED_DYN SECDED_DYN;
+
+// Returns a handle to a SecDedDynamic codec, or NULL if it couldn't be built.
+// The handle must be released with SECDED_DYN_free().
+SECDED_DYN *SECDED_DYN_new(size_t encodable_size);
+
+// Releases secded, which may be NULL.
+void SECDED_DYN_free(SECDED_DYN *secded);
+
+// Returns the number of bits secded encodes, or 0 if it is NULL.
+size_t SECDED_DYN_encodable_size(const SECDED_DYN *secded);
 
-//  De-allocates `secded`'s internals and secded itself.
-void SECDED_DYN_free(const SECDED_DYN *secded);
+// Returns the number of bits of code secded adds to the payload, or 0 if it is NULL.
+size_t SECDED_DYN_code_size(const SECDED_DYN *secded);
 
-//  A wrapper to `secded.encode(&data[..size])`
-void SECDED_DYN_encode(const SECDED_DYN *secded, uint8_t *data, size_t size);
+// Returns the size in bytes of the smallest slice secded works on, or 0 if it is NULL.
+size_t SECDED_DYN_slice_size(const SECDED_DYN *secded);
 
-//  A wrapper to `secded.decode(&data[..size])`
-bool SECDED_DYN_decode(const SECDED_DYN *secded, uint8_t *data, size_t size);
+// Encodes data[..size] in place. The code bits must be set to 0.
+SECDED_STATUS SECDED_DYN_encode(const SECDED_DYN *secded, uint8_t *data, size_t size);
 
+// Decodes data[..size] in place, and resets the code bits to 0.
+SECDED_STATUS SECDED_DYN_decode(const SECDED_DYN *secded, uint8_t *data, size_t size);
+
+// Decodes data[..size] in place like SECDED_DYN_decode(), and describes the outcome in correction,
+// which may be NULL.
+SECDED_STATUS SECDED_DYN_decode_with_correction(const SECDED_DYN *secded, uint8_t *data, size_t size, SECDED_CORRECTION *correction);
+
+// Encodes the count codewords of size bytes stored contiguously in data. Nothing is encoded if any
+// of them has bits set outside of its payload.
+SECDED_STATUS SECDED_DYN_encode_batch(const SECDED_DYN *secded, uint8_t *data, size_t size, size_t count);
+
+// Decodes the count codewords of size bytes stored contiguously in data, and returns the worst
+// status among them. Unless it is NULL, corrections[i] describes the outcome for codeword i.
+SECDED_STATUS SECDED_DYN_decode_batch(const SECDED_DYN *secded, uint8_t *data, size_t size, size_t count, SECDED_CORRECTION *corrections);
+
+// Returns the size in bytes of the blocks SECDED_DYN_encode_blocks() splits length bytes into, or 0 if
+// secded is NULL, can't carry a byte of payload per block, or if that size overflows.
+size_t SECDED_DYN_encoded_size(const SECDED_DYN *secded, size_t length);
+
+// Splits data[..length] into blocks carrying as many whole bytes of payload as secded can encode,
+// and encodes them into blocks[..size], size being SECDED_DYN_encoded_size(secded, length).
+SECDED_STATUS SECDED_DYN_encode_blocks(const SECDED_DYN *secded, const uint8_t *data, size_t length, uint8_t *blocks, size_t size);
+
+// Decodes blocks[..size], produced by SECDED_DYN_encode_blocks(), into data[..length], and returns
+// the worst status among the blocks. The blocks are left untouched.
+SECDED_STATUS SECDED_DYN_decode_blocks(const SECDED_DYN *secded, const uint8_t *blocks, size_t size, uint8_t *data, size_t length);
+
+#endif
+
+#ifdef __cplusplus
+}
+#endif
 #endif
-#endif
\ No newline at end of file
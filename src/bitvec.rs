@@ -26,10 +26,7 @@ impl From<&[u8]> for Bitvec {
     fn from(data: &[u8]) -> Self {
         use std::iter::FromIterator;
         Bitvec(VecDeque::from_iter(
-            data[data
-                .iter()
-                .position(|x| *x != 0)
-                .unwrap_or_else(|| data.len())..]
+            data[data.iter().position(|x| *x != 0).unwrap_or(data.len())..]
                 .iter()
                 .copied(),
         ))
@@ -77,7 +74,17 @@ impl Bitvec {
         self.0.iter().all(|x| *x == 0)
     }
 
-    #[allow(dead_code)]
+    pub fn trailing_zeros(&self) -> usize {
+        let mut count = 0;
+        for byte in self.0.iter().rev() {
+            if *byte != 0 {
+                return count + byte.trailing_zeros() as usize;
+            }
+            count += 8;
+        }
+        count
+    }
+
     pub(crate) fn to_u64_be(&self) -> u64 {
         let mut result = 0;
         for x in self.0.iter() {
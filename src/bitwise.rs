@@ -29,7 +29,7 @@ impl Bitwise for u128 {
         result ^= result >> 4;
         result ^= result >> 2;
         result ^= result >> 1;
-        (result & 1)
+        result & 1
     }
 }
 
@@ -53,7 +53,7 @@ impl Bitwise for u64 {
         result ^= result >> 4;
         result ^= result >> 2;
         result ^= result >> 1;
-        (result & 1)
+        result & 1
     }
 }
 
@@ -74,7 +74,7 @@ impl Bitwise for u8 {
         result ^= result >> 4;
         result ^= result >> 2;
         result ^= result >> 1;
-        (result & 1)
+        result & 1
     }
 }
 
@@ -133,7 +133,7 @@ fn test_parity() {
     assert_eq!([2u8, 2, 8].parity(), 1);
 }
 
-#[cfg(feature = "nightly")]
+#[cfg(feature = "bench")]
 #[bench]
 fn parity_u64(b: &mut test::Bencher) {
     let mut guard = 1;
@@ -145,7 +145,7 @@ fn parity_u64(b: &mut test::Bencher) {
     });
 }
 
-#[cfg(feature = "nightly")]
+#[cfg(feature = "bench")]
 #[bench]
 fn parity_u8_8(b: &mut test::Bencher) {
     let mut guard = 1;
@@ -159,7 +159,7 @@ fn parity_u8_8(b: &mut test::Bencher) {
 
 #[test]
 fn test_count_parity() {
-    for x in (0..100).map(|x| x * (core::u128::MAX / 100)) {
+    for x in (0..100).map(|x| x * (u128::MAX / 100)) {
         assert_eq!(x.parity(), x.count() % 2);
     }
 }
@@ -0,0 +1,303 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Self-contained C sources for a codec, with precomputed tables instead of the Rust library.
+//!
+//! Like `HdlGenerator`, the tables are derived from the codec by encoding each unit payload: the
+//! generated `encode` produces the codec's codewords bit for bit, and `decode` corrects the same single
+//! errors, resets the code bits the same way, and leaves the slice untouched when it reports the errors
+//! as uncorrectable.
+
+use crate::vectors::test_vectors;
+use crate::*;
+use std::fmt::Write;
+
+/// Generates a C source file implementing a codec.
+pub struct CSourceGenerator<'a, C: ?Sized> {
+    codec: &'a C,
+    name: String,
+    word_size: usize,
+    /// The syndrome of a single error on each bit of the codeword.
+    columns: Vec<u64>,
+}
+
+impl<'a, C: SecDedCodec + ?Sized> CSourceGenerator<'a, C> {
+    /// Builds a generator whose functions, tables and macros are prefixed with `name`.
+    /// # Panics:
+    /// Panics if `name` isn't a valid C identifier, or if `codec.code_size() > 16`.
+    pub fn new(codec: &'a C, name: &str) -> Self {
+        let valid = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            panic!("{:?} isn't a valid C identifier", name);
+        }
+        if codec.code_size() > 16 {
+            panic!(
+                "Can't generate C tables for a code of {} bits",
+                codec.code_size()
+            );
+        }
+        CSourceGenerator {
+            codec,
+            name: name.into(),
+            word_size: frame::BlockLayout::of(codec).word_size,
+            columns: syndrome_columns(codec),
+        }
+    }
+
+    /// Returns the bit each syndrome designates plus one, 0 for the syndromes that designate no bit, or
+    /// that several bits share.
+    fn corrections(&self) -> Vec<usize> {
+        let mut corrections = vec![0; 1 << self.codec.code_size()];
+        for (bit, &column) in self.columns.iter().enumerate() {
+            let slot = &mut corrections[column as usize];
+            *slot = if *slot == 0 { bit + 1 } else { usize::MAX };
+        }
+        for slot in corrections.iter_mut() {
+            if *slot == usize::MAX {
+                *slot = 0;
+            }
+        }
+        corrections
+    }
+
+    fn array<T: std::fmt::Display>(values: impl Iterator<Item = T>, per_line: usize) -> String {
+        let values: Vec<String> = values.map(|value| value.to_string()).collect();
+        values
+            .chunks(per_line)
+            .map(|line| format!("    {},\n", line.join(", ")))
+            .collect()
+    }
+
+    fn bytes(slice: &[u8]) -> String {
+        let bytes: Vec<String> = slice.iter().map(|x| format!("0x{:02x}", x)).collect();
+        format!("{{{}}}", bytes.join(", "))
+    }
+
+    /// Returns the C source, whose `<name>_self_test()` checks the generated functions against
+    /// `vector_count` vectors for each error weight from 0 to 2, computed by the codec from random payloads
+    /// drawn from `seed`.
+    ///
+    /// The source declares:
+    /// * `void <name>_encode(uint8_t data[<NAME>_WORD_SIZE])`, which encodes in place like the codec's
+    ///   `encode`, the code bits being set to 0. Unlike it, it doesn't check the slice.
+    /// * `int <name>_decode(uint8_t data[<NAME>_WORD_SIZE])`, which decodes in place like the codec's
+    ///   `decode`, and returns `<NAME>_CLEAN`, `<NAME>_CORRECTED` or `<NAME>_UNCORRECTABLE`, whose values
+    ///   are those of the FFI's `SECDED_STATUS`. It only corrects single errors: those that codecs such as
+    ///   `DecTed` or `SscDsd` correct on several bits are reported as uncorrectable, and the vectors expect it.
+    /// * `int <name>_self_test(void)`, which returns 0 if every vector passes, and the index of the first
+    ///   failing vector plus one otherwise.
+    pub fn source(&self, vector_count: usize, seed: u64) -> String {
+        let (name, upper) = (&self.name, self.name.to_uppercase());
+        let (encodable_size, code_size) = (self.codec.encodable_size(), self.codec.code_size());
+        let corrections = self.corrections();
+        let correction_type = if corrections.len() <= 0x100 && self.columns.len() < 0xff {
+            "uint8_t"
+        } else {
+            "uint16_t"
+        };
+        let vectors = test_vectors(self.codec, vector_count, seed);
+        let mut source = format!(
+            "/* Generated by secded {version} for a codec with {k} bits of payload and {m} bits of code.\n \
+             * Codewords match fingerprint {fingerprint:016x}, and are stored in {w} bytes slices like the\n \
+             * crate's: bit i of a codeword is counted from the right-most bit of its slice, the code taking\n \
+             * bits 0 to {m1}. */\n\n\
+             #include <stddef.h>\n#include <stdint.h>\n\n\
+             #define {upper}_WORD_SIZE {w}\n#define {upper}_ENCODABLE_SIZE {k}\n#define {upper}_CODE_SIZE {m}\n\
+             #define {upper}_CLEAN 0\n#define {upper}_CORRECTED 1\n#define {upper}_UNCORRECTABLE 2\n\n\
+             void {name}_encode(uint8_t data[{upper}_WORD_SIZE]);\n\
+             int {name}_decode(uint8_t data[{upper}_WORD_SIZE]);\n\
+             int {name}_self_test(void);\n\n\
+             /* The syndrome of a single error on each bit of the codeword. */\n\
+             static const uint16_t {name}_columns[{upper}_ENCODABLE_SIZE + {upper}_CODE_SIZE] = {{\n",
+            version = env!("CARGO_PKG_VERSION"),
+            fingerprint = self.codec.fingerprint(),
+            name = name,
+            upper = upper,
+            w = self.word_size,
+            k = encodable_size,
+            m = code_size,
+            m1 = code_size - 1,
+        );
+        source += &Self::array(self.columns.iter().map(|x| format!("0x{:04x}", x)), 8);
+        let _ = write!(
+            source,
+            "}};\n\n/* The bit each syndrome designates plus one, 0 if it designates none. */\n\
+             static const {} {}_corrections[{}] = {{\n",
+            correction_type,
+            name,
+            corrections.len()
+        );
+        source += &Self::array(corrections.iter(), 16);
+        let _ = write!(
+            source,
+            "}};\n\n\
+             static int {name}_bit(const uint8_t data[{upper}_WORD_SIZE], size_t bit) {{\n    \
+             return (data[{upper}_WORD_SIZE - 1 - bit / 8] >> (bit % 8)) & 1;\n}}\n\n\
+             static void {name}_flip(uint8_t data[{upper}_WORD_SIZE], size_t bit) {{\n    \
+             data[{upper}_WORD_SIZE - 1 - bit / 8] ^= (uint8_t)(1u << (bit % 8));\n}}\n\n\
+             static uint16_t {name}_syndrome(const uint8_t data[{upper}_WORD_SIZE]) {{\n    \
+             uint16_t syndrome = 0;\n    \
+             for (size_t bit = 0; bit < {upper}_ENCODABLE_SIZE + {upper}_CODE_SIZE; bit++) {{\n        \
+             if ({name}_bit(data, bit)) {{\n            syndrome ^= {name}_columns[bit];\n        }}\n    }}\n    \
+             return syndrome;\n}}\n\n\
+             void {name}_encode(uint8_t data[{upper}_WORD_SIZE]) {{\n    \
+             uint16_t code = {name}_syndrome(data);\n    \
+             for (size_t bit = 0; bit < {upper}_CODE_SIZE; bit++) {{\n        \
+             if ((code >> bit) & 1) {{\n            {name}_flip(data, bit);\n        }}\n    }}\n}}\n\n\
+             int {name}_decode(uint8_t data[{upper}_WORD_SIZE]) {{\n    \
+             uint16_t syndrome = {name}_syndrome(data);\n    int status = {upper}_CLEAN;\n    \
+             if (syndrome != 0) {{\n        size_t bit = {name}_corrections[syndrome];\n        \
+             if (bit == 0) {{\n            return {upper}_UNCORRECTABLE;\n        }}\n        \
+             {name}_flip(data, bit - 1);\n        status = {upper}_CORRECTED;\n    }}\n    \
+             for (size_t bit = 0; bit < {upper}_CODE_SIZE; bit++) {{\n        \
+             if ({name}_bit(data, bit)) {{\n            {name}_flip(data, bit);\n        }}\n    }}\n    \
+             return status;\n}}\n\n\
+             /* Vectors computed by the crate: the payload, its codeword, the received codeword, and the\n \
+             * slice once decoded. */\n\
+             static const uint8_t {name}_vectors[{count}][4][{upper}_WORD_SIZE] = {{\n",
+            name = name,
+            upper = upper,
+            count = vectors.len()
+        );
+        for vector in vectors.iter() {
+            let _ = writeln!(
+                source,
+                "    {{{}, {}, {}, {}}},",
+                Self::bytes(&vector.payload),
+                Self::bytes(&vector.codeword),
+                Self::bytes(&vector.received),
+                Self::bytes(&vector.decoded)
+            );
+        }
+        let _ = write!(
+            source,
+            "}};\n\nstatic const uint8_t {name}_statuses[{count}] = {{\n",
+            name = name,
+            count = vectors.len()
+        );
+        source += &Self::array(vectors.iter().map(|vector| vector.status), 24);
+        let _ = write!(
+            source,
+            "}};\n\n\
+             static int {name}_equal(const uint8_t *a, const uint8_t *b) {{\n    \
+             for (size_t i = 0; i < {upper}_WORD_SIZE; i++) {{\n        \
+             if (a[i] != b[i]) {{\n            return 0;\n        }}\n    }}\n    return 1;\n}}\n\n\
+             int {name}_self_test(void) {{\n    \
+             for (size_t i = 0; i < {count}; i++) {{\n        \
+             uint8_t buffer[{upper}_WORD_SIZE];\n        \
+             for (size_t j = 0; j < {upper}_WORD_SIZE; j++) {{\n            \
+             buffer[j] = {name}_vectors[i][0][j];\n        }}\n        \
+             {name}_encode(buffer);\n        \
+             if (!{name}_equal(buffer, {name}_vectors[i][1])) {{\n            return (int)i + 1;\n        }}\n        \
+             for (size_t j = 0; j < {upper}_WORD_SIZE; j++) {{\n            \
+             buffer[j] = {name}_vectors[i][2][j];\n        }}\n        \
+             if ({name}_decode(buffer) != {name}_statuses[i] || !{name}_equal(buffer, {name}_vectors[i][3])) {{\n            \
+             return (int)i + 1;\n        }}\n    }}\n    return 0;\n}}\n",
+            name = name,
+            upper = upper,
+            count = vectors.len()
+        );
+        source
+    }
+}
+
+#[test]
+fn tables_match_codecs() {
+    let codecs: Vec<Box<dyn SecDedCodec>> = vec![
+        Box::new(SecDed64::new(57)),
+        Box::new(SecDed128::new(120)),
+        Box::new(SecDed128::new_sbd(100)),
+        Box::new(Hamming::new(11)),
+        Box::new(Parity::new(7)),
+        Box::new(DecTed::new(16)),
+        Box::new(SscDsd::new(4, 16)),
+    ];
+    for codec in codecs.iter() {
+        let generator = CSourceGenerator::new(codec.as_ref(), "secded");
+        let corrections = generator.corrections();
+        // Runs the generated `decode` on the tables.
+        for vector in test_vectors(codec.as_ref(), 100, 48) {
+            let mut buffer = vector.received.clone();
+            let syndrome = generator
+                .columns
+                .iter()
+                .enumerate()
+                .filter(|&(bit, _)| nth_bit_from_right(&buffer, bit) == 1)
+                .fold(0, |syndrome, (_, column)| syndrome ^ column);
+            let status = match (syndrome, corrections[syndrome as usize]) {
+                (0, _) => 0,
+                (_, 0) => 2,
+                (_, bit) => {
+                    fault::flip_bit(&mut buffer, bit - 1);
+                    1
+                }
+            };
+            if status != 2 {
+                for bit in 0..codec.code_size() {
+                    if nth_bit_from_right(&buffer, bit) == 1 {
+                        fault::flip_bit(&mut buffer, bit);
+                    }
+                }
+            }
+            assert_eq!((status, buffer), (vector.status, vector.decoded));
+        }
+    }
+}
+
+#[test]
+fn source() {
+    let codec = SecDed64::new(4);
+    let source = CSourceGenerator::new(&codec, "tiny").source(2, 0);
+    assert!(source.contains("#define TINY_WORD_SIZE 8\n"));
+    assert!(source.contains("static const uint8_t tiny_corrections[16] = {\n"));
+    assert!(source.contains("static const uint8_t tiny_vectors[6][4][TINY_WORD_SIZE] = {\n"));
+    assert_eq!(source.matches('{').count(), source.matches('}').count());
+}
+
+#[test]
+fn compiled_self_test() {
+    use std::process::Command;
+    let compiler = std::env::var("CC").unwrap_or_else(|_| "cc".into());
+    let directory = std::env::temp_dir().join(format!("secded-c-source-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let codecs: Vec<Box<dyn SecDedCodec>> = vec![
+        Box::new(SecDed64::new(4)),
+        Box::new(SecDed64::new(57)),
+        Box::new(SecDed128::new(120)),
+        Box::new(DecTed::new(16)),
+        Box::new(SscDsd::new(4, 16)),
+    ];
+    for (i, codec) in codecs.iter().enumerate() {
+        let name = format!("codec_{}", i);
+        let source = directory.join(format!("{}.c", name));
+        let mut program = CSourceGenerator::new(codec.as_ref(), &name).source(20, i as u64);
+        let _ = write!(
+            program,
+            "\nint main(void) {{\n    return {}_self_test() == 0 ? 0 : 1;\n}}\n",
+            name
+        );
+        std::fs::write(&source, program).unwrap();
+        let binary = directory.join(&name);
+        let compiled = match Command::new(&compiler)
+            .args(["-std=c99", "-Wall", "-Wextra", "-pedantic", "-Werror", "-o"])
+            .arg(&binary)
+            .arg(&source)
+            .status()
+        {
+            Ok(status) => status,
+            Err(e) => {
+                eprintln!(
+                    "skipping compiled_self_test, `{}` can't be run: {}",
+                    compiler, e
+                );
+                return;
+            }
+        };
+        assert!(compiled.success(), "{} doesn't compile", source.display());
+        let status = Command::new(&binary).status().unwrap();
+        assert!(status.success(), "{} fails its self test", source.display());
+    }
+    let _ = std::fs::remove_dir_all(&directory);
+}
@@ -0,0 +1,231 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::fault::flip_bit;
+use crate::*;
+
+/// A non-reflected CRC of 8, 16 or 32 bits, computed MSB first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crc {
+    width: u8,
+    polynomial: u32,
+    init: u32,
+}
+
+impl Crc {
+    pub const CRC8: Crc = Crc {
+        width: 8,
+        polynomial: 0x07,
+        init: 0,
+    };
+    pub const CRC16_CCITT: Crc = Crc {
+        width: 16,
+        polynomial: 0x1021,
+        init: 0xffff,
+    };
+    pub const CRC32: Crc = Crc {
+        width: 32,
+        polynomial: 0x04c1_1db7,
+        init: 0xffff_ffff,
+    };
+
+    /// # Panics:
+    /// Panics if `width` isn't 8, 16 or 32.
+    pub fn new(width: u8, polynomial: u32, init: u32) -> Self {
+        match width {
+            8 | 16 | 32 => {}
+            _ => panic!("CRCs can only be 8, 16 or 32 bits wide, not {}", width),
+        }
+        let mask = Self::mask_of(width);
+        Crc {
+            width,
+            polynomial: polynomial & mask,
+            init: init & mask,
+        }
+    }
+
+    fn mask_of(width: u8) -> u32 {
+        (1u64 << width).wrapping_sub(1) as u32
+    }
+
+    pub fn width(&self) -> usize {
+        self.width as usize
+    }
+
+    pub fn checksum(&self, data: &[u8]) -> u32 {
+        let top = 1u32 << (self.width - 1);
+        let mut crc = self.init;
+        for &byte in data {
+            crc ^= u32::from(byte) << (self.width - 8);
+            for _ in 0..8 {
+                crc = if crc & top != 0 {
+                    (crc << 1) ^ self.polynomial
+                } else {
+                    crc << 1
+                };
+            }
+        }
+        crc & Self::mask_of(self.width)
+    }
+}
+
+/// Reasons for which `WithCrc::decode_crc` may reject a codeword.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrcError {
+    /// The inner codec detected at least 2 errors.
+    Uncorrectable { syndrome: u64 },
+    /// The inner codec accepted the codeword, but the CRC doesn't match: at least 3 errors occurred and the
+    /// codeword was miscorrected. The content of the slice must not be used.
+    Miscorrected,
+}
+
+/// Concatenates a CRC with any codec: the CRC of the payload is carried in the `crc.width()` bits right above
+/// the inner codec's correction code, and checked after correction to catch the miscorrections of patterns
+/// of 3 errors or more.
+///
+/// `WithCrc` is a codec itself, whose `code_size()` includes the CRC.
+pub struct WithCrc<C> {
+    codec: C,
+    crc: Crc,
+}
+
+impl<C: SecDedCodec> WithCrc<C> {
+    /// # Panics:
+    /// Panics if `codec` can't carry the CRC.
+    pub fn new(codec: C, crc: Crc) -> Self {
+        if codec.encodable_size() <= crc.width() {
+            panic!(
+                "A codec with an encodable size of {} bits can't carry a {} bits CRC",
+                codec.encodable_size(),
+                crc.width()
+            );
+        }
+        WithCrc { codec, crc }
+    }
+
+    pub fn codec(&self) -> &C {
+        &self.codec
+    }
+
+    pub fn crc(&self) -> &Crc {
+        &self.crc
+    }
+
+    /// Reads and clears the CRC bits.
+    fn take_crc(&self, data: &mut [u8]) -> u32 {
+        let code_size = self.codec.code_size();
+        let mut crc = 0;
+        for i in 0..self.crc.width() {
+            if nth_bit_from_right(data, code_size + i) == 1 {
+                crc |= 1 << i;
+                flip_bit(data, code_size + i);
+            }
+        }
+        crc
+    }
+
+    /// Decodes the data IN-PLACE, then checks the CRC.
+    /// The last `code_size()` bits, which include the CRC, are reset to 0 if decoding succeeds.
+    pub fn decode_crc(&self, data: &mut [u8]) -> Result<Correction, CrcError> {
+        let correction = self
+            .codec
+            .decode_with_correction(data)
+            .map_err(|Uncorrectable { syndrome }| CrcError::Uncorrectable { syndrome })?;
+        let received = self.take_crc(data);
+        if received == self.crc.checksum(data) {
+            Ok(correction)
+        } else {
+            Err(CrcError::Miscorrected)
+        }
+    }
+}
+
+impl<C: SecDedCodec> SecDedCodec for WithCrc<C> {
+    fn encodable_size(&self) -> usize {
+        self.codec.encodable_size() - self.crc.width()
+    }
+    fn code_size(&self) -> usize {
+        self.codec.code_size() + self.crc.width()
+    }
+    fn expected_slice_size(&self) -> Option<usize> {
+        self.codec.expected_slice_size()
+    }
+
+    /// Encodes the data IN-PLACE
+    /// # Arguments:
+    /// * `data`: The slice of data to encode. The last `code_size()` bits, which include the CRC, MUST be
+    ///   set to 0.
+    fn encode(&self, data: &mut [u8]) {
+        let crc = self.crc.checksum(data);
+        let code_size = self.codec.code_size();
+        for i in 0..self.crc.width() {
+            if (crc >> i) & 1 == 1 {
+                flip_bit(data, code_size + i);
+            }
+        }
+        self.codec.encode(data)
+    }
+
+    /// Decodes the data IN-PLACE, see `decode_crc`.
+    /// `Err(())` if 2 errors were detected, or if the CRC revealed a miscorrection.
+    fn decode(&self, data: &mut [u8]) -> Result<(), ()> {
+        match self.decode_crc(data) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(()),
+        }
+    }
+
+    /// Since `Uncorrectable` can't tell miscorrections apart, their `syndrome` is reported as 0: use
+    /// `decode_crc` to distinguish them.
+    fn decode_with_correction(&self, data: &mut [u8]) -> Result<Correction, Uncorrectable> {
+        self.decode_crc(data).map_err(|error| match error {
+            CrcError::Uncorrectable { syndrome } => Uncorrectable { syndrome },
+            CrcError::Miscorrected => Uncorrectable { syndrome: 0 },
+        })
+    }
+}
+
+#[test]
+fn checksums() {
+    assert_eq!(Crc::CRC8.checksum(b"123456789"), 0xf4);
+    assert_eq!(Crc::CRC16_CCITT.checksum(b"123456789"), 0x29b1);
+    assert_eq!(
+        Crc::new(32, 0x04c1_1db7, 0).checksum(b"123456789"),
+        0x89a1_897f
+    );
+}
+
+#[test]
+fn triple_errors() {
+    use crate::fault::{FaultInjector, FaultModel};
+    let plain = SecDed64::new(57);
+    let secded = WithCrc::new(SecDed64::new(57), Crc::CRC16_CCITT);
+    assert_eq!(secded.encodable_size(), 41);
+    let expected = [1, 2, 3, 4, 5, 0, 0, 0];
+    let (mut plain_buffer, mut encode_buffer) = (expected, expected);
+    plain.encode(&mut plain_buffer);
+    secded.encode(&mut encode_buffer);
+    let mut buffer = encode_buffer;
+    buffer[1] ^= 1 << 5;
+    assert_eq!(secded.decode(&mut buffer), Ok(()));
+    assert_eq!(buffer, expected);
+    let (mut injector, mut miscorrections) = (FaultInjector::new(35), 0);
+    for _ in 0..2000 {
+        let mut local_buffer = plain_buffer;
+        let injection =
+            injector.inject_codeword(&plain, &mut local_buffer, &FaultModel::RandomBits(3));
+        if plain.decode(&mut local_buffer).is_ok() && local_buffer != expected {
+            miscorrections += 1;
+        }
+        let mut local_buffer = encode_buffer;
+        for &bit in injection.flipped.iter() {
+            flip_bit(&mut local_buffer, bit);
+        }
+        match secded.decode_crc(&mut local_buffer) {
+            Ok(_) => panic!("{:?} went undetected", injection),
+            Err(CrcError::Miscorrected) | Err(CrcError::Uncorrectable { .. }) => {}
+        }
+    }
+    assert!(miscorrections > 0);
+}
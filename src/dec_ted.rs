@@ -0,0 +1,255 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A double-error-correcting, triple-error-detecting codec: a shortened binary BCH code over `GF(2^m)`,
+//! extended with an overall parity bit.
+//!
+//! Bit 0 of the slice is the overall parity bit, and bit `i + 1` holds the coefficient of `x^i` in the BCH
+//! codeword's polynomial, whose `code_size() - 1` lowest coefficients are the remainder of the payload by
+//! the generator polynomial `g = m1 * m3`. The minimum distance of the extended code is 6.
+
+use crate::fault::flip_bit;
+use crate::galois::{GaloisField, PRIMITIVE_POLYNOMIALS};
+use crate::*;
+
+/// The supported word sizes, in bits.
+const WORD_SIZES: [usize; 5] = [32, 64, 128, 256, 512];
+
+/// A DEC-TED codec for words of 32 to 512 bits.
+pub struct DecTed {
+    encodable_size: usize,
+    /// The degree of the generator polynomial.
+    degree: usize,
+    generator: u32,
+    word_size: usize,
+    field: GaloisField,
+}
+
+impl DecTed {
+    /// Builds a codec for `encodable_size` bits payloads, stored in the smallest word of 32, 64, 128, 256
+    /// or 512 bits that can hold them along with the code.
+    /// # Panics:
+    /// Panics if `encodable_size` is 0, or if a 512 bits word can't hold the payload and its code.
+    pub fn new(encodable_size: usize) -> Self {
+        if encodable_size == 0 {
+            panic!("DecTed can't encode empty payloads");
+        }
+        // The shortened BCH code must fit in the `2^m - 1` bits of the full code.
+        let m = (5..PRIMITIVE_POLYNOMIALS.len())
+            .find(|&m| encodable_size + 2 * m < (1 << m))
+            .unwrap_or_else(|| {
+                panic!(
+                    "DecTed can't encode payloads of {} bits in 512 bits words",
+                    encodable_size
+                )
+            });
+        let field = GaloisField::new(m);
+        let (m1, m3) = (field.minimal_polynomial(1), field.minimal_polynomial(3));
+        let mut generator = 0u32;
+        for i in 0..32 {
+            if (m3 >> i) & 1 == 1 {
+                generator ^= m1 << i;
+            }
+        }
+        let degree = 31 - generator.leading_zeros() as usize;
+        let total_size = encodable_size + degree + 1;
+        let word_size = match WORD_SIZES.iter().find(|&&size| size >= total_size) {
+            Some(size) => size / 8,
+            None => panic!(
+                "DecTed can't encode payloads of {} bits in 512 bits words",
+                encodable_size
+            ),
+        };
+        DecTed {
+            encodable_size,
+            degree,
+            generator,
+            word_size,
+            field,
+        }
+    }
+
+    fn check_size(&self, data: &[u8]) {
+        if data.len() != self.word_size {
+            panic!(
+                "This DecTed codec works on {} bytes slices, not {}",
+                self.word_size,
+                data.len()
+            );
+        }
+    }
+
+    /// Returns `S1 = r(alpha)` and `S3 = r(alpha^3)`, `r` being the BCH part of the codeword.
+    fn syndromes(&self, data: &[u8]) -> (u16, u16) {
+        let mut syndromes = (0, 0);
+        for i in 0..(self.encodable_size + self.degree) {
+            if nth_bit_from_right(data, i + 1) == 1 {
+                syndromes.0 ^= self.field.alpha(i);
+                syndromes.1 ^= self.field.alpha(3 * i);
+            }
+        }
+        syndromes
+    }
+
+    /// Returns the index in the slice of the error located by `locator`, if it lands in the shortened code.
+    fn position(&self, locator: u16) -> Option<usize> {
+        let i = self.field.log[locator as usize] as usize;
+        if i < self.encodable_size + self.degree {
+            Some(i + 1)
+        } else {
+            None
+        }
+    }
+
+    /// Finds the positions of the 2 errors whose syndromes are `s1` and `s3`, with `s1 != 0`.
+    fn locate_pair(&self, s1: u16, s3: u16) -> Option<[usize; 2]> {
+        // The locators are the roots of `x^2 + s1 * x + (s3 + s1^3) / s1`.
+        let s1_cubed = self.field.mul(s1, self.field.mul(s1, s1));
+        let product = self.field.div(s3 ^ s1_cubed, s1);
+        let mut roots = (0..(self.encodable_size + self.degree)).filter(|&i| {
+            let x = self.field.alpha(i);
+            self.field.mul(x, x) ^ self.field.mul(s1, x) ^ product == 0
+        });
+        match (roots.next(), roots.next()) {
+            (Some(a), Some(b)) => Some([a + 1, b + 1]),
+            _ => None,
+        }
+    }
+}
+
+impl SecDedCodec for DecTed {
+    fn encodable_size(&self) -> usize {
+        self.encodable_size
+    }
+    fn code_size(&self) -> usize {
+        self.degree + 1
+    }
+    fn expected_slice_size(&self) -> Option<usize> {
+        Some(self.word_size)
+    }
+
+    /// Encodes the data IN-PLACE
+    /// # Panics:
+    /// Panics if `data.len() != self.expected_slice_size().unwrap()`
+    fn encode(&self, data: &mut [u8]) {
+        self.check_size(data);
+        let mask = (1u32 << self.degree) - 1;
+        let mut remainder = 0u32;
+        for i in (0..self.encodable_size).rev() {
+            let bit = u32::from(nth_bit_from_right(data, self.code_size() + i));
+            let feedback = bit ^ (remainder >> (self.degree - 1));
+            remainder = (remainder << 1) & mask;
+            if feedback == 1 {
+                remainder ^= self.generator & mask;
+            }
+        }
+        for i in 0..self.degree {
+            if (remainder >> i) & 1 == 1 {
+                flip_bit(data, i + 1);
+            }
+        }
+        let parity = data.iter().fold(0, |parity, x| parity ^ x.count_ones()) & 1;
+        if parity == 1 {
+            flip_bit(data, 0);
+        }
+    }
+
+    /// Decodes the data IN-PLACE, correcting up to 2 errors and detecting 3.
+    /// # Panics:
+    /// Panics if `data.len() != self.expected_slice_size().unwrap()`
+    fn decode(&self, data: &mut [u8]) -> Result<(), ()> {
+        match self.decode_with_correction(data) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(()),
+        }
+    }
+
+    /// The reported syndromes pack the overall parity in bit 32, `S3` in bits 16 to 31, and `S1` in the
+    /// lowest 16 bits.
+    fn decode_with_correction(&self, data: &mut [u8]) -> Result<Correction, Uncorrectable> {
+        self.check_size(data);
+        let (s1, s3) = self.syndromes(data);
+        let parity = data.iter().fold(0, |parity, x| parity ^ x.count_ones()) & 1;
+        let syndrome = u64::from(parity) << 32 | u64::from(s3) << 16 | u64::from(s1);
+        let single = s1 != 0 && s3 == self.field.mul(s1, self.field.mul(s1, s1));
+        let correction = match (parity, s1, s3) {
+            (0, 0, 0) => Some(Correction::None),
+            (1, 0, 0) => Some(Correction::Bit { bit: 0, syndrome }),
+            (1, _, _) if single => self
+                .position(s1)
+                .map(|bit| Correction::Bit { bit, syndrome }),
+            (0, _, _) if single => self.position(s1).map(|bit| Correction::Double {
+                bits: [0, bit],
+                syndrome,
+            }),
+            (0, 0, _) => None,
+            (0, _, _) => self
+                .locate_pair(s1, s3)
+                .map(|bits| Correction::Double { bits, syndrome }),
+            _ => None,
+        };
+        let correction = correction.ok_or(Uncorrectable { syndrome })?;
+        for bit in correction.bits() {
+            flip_bit(data, bit);
+        }
+        for bit in 0..self.code_size() {
+            if nth_bit_from_right(data, bit) == 1 {
+                flip_bit(data, bit);
+            }
+        }
+        Ok(correction)
+    }
+}
+
+#[test]
+fn codec() {
+    for &(encodable_size, word_size) in [(21, 4), (50, 8), (111, 16), (200, 32), (492, 64)].iter() {
+        let dec_ted = DecTed::new(encodable_size);
+        assert_eq!(dec_ted.expected_slice_size(), Some(word_size));
+        let total_size = encodable_size + dec_ted.code_size();
+        let mut expected = vec![0u8; word_size];
+        for bit in (dec_ted.code_size()..total_size).step_by(5) {
+            flip_bit(&mut expected, bit);
+        }
+        let mut encoded = expected.clone();
+        dec_ted.encode(&mut encoded);
+        let mut buffer = encoded.clone();
+        assert_eq!(
+            dec_ted.decode_with_correction(&mut buffer),
+            Ok(Correction::None)
+        );
+        assert_eq!(buffer, expected);
+        let step = if total_size > 128 { 7 } else { 1 };
+        for i in (0..total_size).step_by(step) {
+            for j in ((i + 1)..total_size).step_by(step) {
+                let mut buffer = encoded.clone();
+                flip_bit(&mut buffer, i);
+                flip_bit(&mut buffer, j);
+                match dec_ted.decode_with_correction(&mut buffer) {
+                    Ok(Correction::Double { bits, .. }) => assert_eq!(bits, [i, j]),
+                    other => panic!("{:?} for errors on {} and {}", other, i, j),
+                }
+                assert_eq!(buffer, expected);
+            }
+            let mut buffer = encoded.clone();
+            flip_bit(&mut buffer, i);
+            assert_eq!(dec_ted.decode(&mut buffer), Ok(()));
+            assert_eq!(buffer, expected);
+        }
+    }
+}
+
+#[test]
+fn triple_errors() {
+    use crate::fault::{FaultInjector, FaultModel};
+    let (dec_ted, mut injector) = (DecTed::new(111), FaultInjector::new(40));
+    let mut encoded = [0u8; 16];
+    encoded[3] = 0x5a;
+    dec_ted.encode(&mut encoded);
+    for _ in 0..20000 {
+        let mut buffer = encoded;
+        let injection = injector.inject_codeword(&dec_ted, &mut buffer, &FaultModel::RandomBits(3));
+        assert!(dec_ted.decode(&mut buffer).is_err(), "{:?}", injection);
+    }
+}
@@ -0,0 +1,149 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Erasure decoding for any codec with a minimum distance of 4.
+//!
+//! An erasure is a bit whose value is known to be unreliable. Every filling of the erased bits is decoded,
+//! and only the fillings which decode to a codeword that differs from the received data on erased bits
+//! alone are kept. Since two codewords differ in at least 4 bits, `e` erasures and `s` other errors with
+//! `e + s < 4` can never yield a wrong codeword, which gives the guarantees of
+//! `SecDedCodec::decode_with_erasures`.
+
+use crate::fault::flip_bit;
+use crate::*;
+
+/// The maximum number of erasures `decode_with_erasures` accepts.
+pub const MAX_ERASURES: usize = 3;
+
+/// Reasons for which `SecDedCodec::decode_with_erasures` may reject a codeword.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErasureError {
+    /// More than `MAX_ERASURES` distinct bits were marked as erased.
+    TooManyErasures(usize),
+    /// No single filling of the erased bits is consistent with the rest of the codeword: errors occurred
+    /// outside of the erasures.
+    Uncorrectable,
+}
+
+pub(crate) fn decode_with_erasures<C: SecDedCodec + ?Sized>(
+    codec: &C,
+    data: &mut [u8],
+    erasures: &[usize],
+) -> Result<Vec<usize>, ErasureError> {
+    let total_size = codec.encodable_size() + codec.code_size();
+    let mut erasures = erasures.to_vec();
+    erasures.sort_unstable();
+    erasures.dedup();
+    if let Some(&bit) = erasures.iter().find(|&&bit| bit >= total_size) {
+        panic!("Erasure {} is out of the {} bits codeword", bit, total_size);
+    }
+    if erasures.is_empty() {
+        return match codec.decode_with_correction(data) {
+            Ok(correction) => Ok(correction.bits()),
+            Err(_) => Err(ErasureError::Uncorrectable),
+        };
+    }
+    if erasures.len() > MAX_ERASURES {
+        return Err(ErasureError::TooManyErasures(erasures.len()));
+    }
+    let mut found: Option<(Vec<usize>, Vec<u8>)> = None;
+    for filling in 0..(1usize << erasures.len()) {
+        let mut buffer = data.to_vec();
+        let mut flipped: Vec<usize> = erasures
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| (filling >> i) & 1 == 1)
+            .map(|(_, &bit)| bit)
+            .collect();
+        for &bit in flipped.iter() {
+            flip_bit(&mut buffer, bit);
+        }
+        match codec.decode_with_correction(&mut buffer) {
+            Ok(correction) if correction.bits().iter().all(|bit| erasures.contains(bit)) => {
+                for bit in correction.bits() {
+                    match flipped.iter().position(|&flip| flip == bit) {
+                        Some(i) => {
+                            flipped.remove(i);
+                        }
+                        None => flipped.push(bit),
+                    }
+                }
+                flipped.sort_unstable();
+            }
+            _ => continue,
+        }
+        match &found {
+            Some((previous, _)) if *previous != flipped => return Err(ErasureError::Uncorrectable),
+            Some(_) => {}
+            None => found = Some((flipped, buffer)),
+        }
+    }
+    let (flipped, buffer) = found.ok_or(ErasureError::Uncorrectable)?;
+    data.copy_from_slice(&buffer);
+    Ok(flipped)
+}
+
+/// Checks every pair of erasures, along with one in `stride` of the possible additional errors.
+#[cfg(test)]
+fn check_erasures<C: SecDedCodec>(codec: &C, word_size: usize, stride: usize) {
+    let total_size = codec.encodable_size() + codec.code_size();
+    let mut expected = vec![0u8; word_size];
+    for bit in (codec.code_size()..total_size).step_by(3) {
+        flip_bit(&mut expected, bit);
+    }
+    let mut encoded = expected.clone();
+    codec.encode(&mut encoded);
+    let decode = |flips: &[usize], erasures: &[usize]| {
+        let mut buffer = encoded.clone();
+        for &bit in flips {
+            flip_bit(&mut buffer, bit);
+        }
+        let result = codec.decode_with_erasures(&mut buffer, erasures);
+        if result.is_ok() {
+            assert_eq!(buffer, expected, "{:?} {:?}", flips, erasures);
+        }
+        result
+    };
+    for i in 0..total_size {
+        for j in (i + 1)..total_size {
+            // Both erased bits are recovered, whatever their received value.
+            for &flips in [&[][..], &[i], &[j], &[i, j]].iter() {
+                assert_eq!(decode(flips, &[i, j]), Ok(flips.to_vec()));
+            }
+            // One erasure plus another error, or two erasures plus another error, are detected.
+            assert_eq!(decode(&[j], &[i]), Err(ErasureError::Uncorrectable));
+            assert_eq!(decode(&[i, j], &[i]), Err(ErasureError::Uncorrectable));
+            for k in (0..total_size)
+                .step_by(stride)
+                .filter(|&k| k != i && k != j)
+            {
+                assert_eq!(decode(&[k], &[i, j]), Err(ErasureError::Uncorrectable));
+                assert_eq!(decode(&[j, k], &[i]), Err(ErasureError::Uncorrectable));
+            }
+        }
+    }
+}
+
+#[test]
+fn erasures() {
+    check_erasures(&SecDed64::new(57), 8, 1);
+    check_erasures(&SecDed64::new(20), 8, 1);
+    check_erasures(&SecDed128::new(120), 16, 9);
+    #[cfg(feature = "dyn")]
+    check_erasures(&SecDedDynamic::new(70), 10, 5);
+    let secded = SecDed64::new(57);
+    let mut buffer = [0, 0, 0, 0, 5, 0, 0, 0];
+    secded.encode(&mut buffer);
+    buffer[0] ^= 0b0111_0000;
+    let mut erased = buffer;
+    assert_eq!(
+        secded.decode_with_erasures(&mut erased, &[62, 61, 60, 62]),
+        Ok(vec![60, 61, 62])
+    );
+    assert_eq!(erased, [0, 0, 0, 0, 5, 0, 0, 0]);
+    assert_eq!(
+        secded.decode_with_erasures(&mut buffer, &[0, 1, 2, 3]),
+        Err(ErasureError::TooManyErasures(4))
+    );
+}
@@ -0,0 +1,238 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Reproducible fault injection into encoded buffers.
+//!
+//! Bit indices are always counted from the right-most bit of the buffer, like in `Correction::Bit`.
+
+use crate::*;
+
+/// A small SplitMix64 generator: fast, seedable, and stable across versions of this crate, so that
+/// injections can be replayed from their seed.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a uniformly distributed integer in `0..n`.
+    pub fn below(&mut self, n: usize) -> usize {
+        ((u128::from(self.next_u64()) * n as u128) >> 64) as usize
+    }
+
+    /// Returns a uniformly distributed float in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Flips the `bit`th bit of `buffer`, counted from its right-most bit.
+pub fn flip_bit(buffer: &mut [u8], bit: usize) {
+    let len = buffer.len();
+    buffer[len - 1 - bit / 8] ^= 1 << (bit % 8);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FaultModel {
+    /// Flips `k` distinct bits, chosen uniformly.
+    RandomBits(usize),
+    /// Flips `length` adjacent bits, starting at a uniformly chosen position.
+    Burst(usize),
+    /// Forces the bits set in `mask` to `value`, `mask` being aligned with the buffer.
+    /// Only the bits that actually changed are recorded as flipped.
+    StuckAt { mask: Vec<u8>, value: bool },
+    /// Flips each bit independently with the given probability.
+    BitErrorRate(f64),
+    /// Flips a random non-empty set of bits within one symbol of `size` bits, the buffer being split into
+    /// symbols from its right-most bit: this models the failure of a whole memory chip.
+    Symbol(usize),
+}
+
+impl FaultModel {
+    pub fn single() -> Self {
+        FaultModel::RandomBits(1)
+    }
+
+    pub fn double() -> Self {
+        FaultModel::RandomBits(2)
+    }
+}
+
+/// The bits flipped by a `FaultInjector`, in increasing order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Injection {
+    pub flipped: Vec<usize>,
+}
+
+impl Injection {
+    pub fn weight(&self) -> usize {
+        self.flipped.len()
+    }
+}
+
+/// Injects errors following a `FaultModel`; two injectors built from the same seed flip the same bits.
+pub struct FaultInjector {
+    rng: Rng,
+}
+
+impl FaultInjector {
+    pub fn new(seed: u64) -> Self {
+        FaultInjector {
+            rng: Rng::new(seed),
+        }
+    }
+
+    pub fn rng(&mut self) -> &mut Rng {
+        &mut self.rng
+    }
+
+    /// Injects errors anywhere in `buffer`.
+    pub fn inject(&mut self, buffer: &mut [u8], model: &FaultModel) -> Injection {
+        let width = buffer.len() * 8;
+        self.inject_bits(buffer, width, model)
+    }
+
+    /// Injects errors in the `encodable_size() + code_size()` right-most bits of `buffer`, which are the
+    /// only ones `codec` reads.
+    pub fn inject_codeword<C: SecDedCodec + ?Sized>(
+        &mut self,
+        codec: &C,
+        buffer: &mut [u8],
+        model: &FaultModel,
+    ) -> Injection {
+        let width = codec.encodable_size() + codec.code_size();
+        self.inject_bits(buffer, width, model)
+    }
+
+    /// Injects errors in the `width` right-most bits of `buffer`.
+    /// # Panics:
+    /// Panics if `width` is larger than the buffer, or too small for the model.
+    pub fn inject_bits(
+        &mut self,
+        buffer: &mut [u8],
+        width: usize,
+        model: &FaultModel,
+    ) -> Injection {
+        if width > buffer.len() * 8 {
+            panic!(
+                "Can't inject errors in {} bits of a {} bytes long buffer",
+                width,
+                buffer.len()
+            );
+        }
+        let mut flipped = Vec::new();
+        match model {
+            FaultModel::RandomBits(k) => {
+                if *k > width {
+                    panic!("Can't flip {} distinct bits out of {}", k, width);
+                }
+                while flipped.len() < *k {
+                    let bit = self.rng.below(width);
+                    if !flipped.contains(&bit) {
+                        flipped.push(bit);
+                    }
+                }
+            }
+            FaultModel::Burst(length) => {
+                if *length > width {
+                    panic!("Can't fit a burst of {} bits in {}", length, width);
+                }
+                let start = self.rng.below(width - length + 1);
+                flipped.extend(start..start + length);
+            }
+            FaultModel::StuckAt { mask, value } => {
+                if mask.len() != buffer.len() {
+                    panic!(
+                        "A {} bytes long mask can't be applied to a {} bytes long buffer",
+                        mask.len(),
+                        buffer.len()
+                    );
+                }
+                flipped.extend((0..width).filter(|&bit| {
+                    nth_bit_from_right(mask, bit) == 1
+                        && (nth_bit_from_right(buffer, bit) == 1) != *value
+                }));
+            }
+            FaultModel::BitErrorRate(p) => {
+                flipped.extend((0..width).filter(|_| self.rng.next_f64() < *p));
+            }
+            FaultModel::Symbol(size) => {
+                if *size == 0 || *size > width || *size > 32 {
+                    panic!("Can't corrupt symbols of {} bits out of {}", size, width);
+                }
+                let symbol = self.rng.below(width / size);
+                let error = 1 + self.rng.below((1 << size) - 1);
+                flipped.extend(
+                    (0..*size)
+                        .filter(|i| (error >> i) & 1 == 1)
+                        .map(|i| symbol * size + i),
+                );
+            }
+        }
+        flipped.sort_unstable();
+        for &bit in flipped.iter() {
+            flip_bit(buffer, bit);
+        }
+        Injection { flipped }
+    }
+}
+
+#[test]
+fn reproducible() {
+    let models = [
+        FaultModel::RandomBits(3),
+        FaultModel::Burst(4),
+        FaultModel::BitErrorRate(0.1),
+        FaultModel::Symbol(4),
+    ];
+    for model in models.iter() {
+        let (mut a, mut b) = ([0u8; 16], [0u8; 16]);
+        let (mut x, mut y) = (FaultInjector::new(42), FaultInjector::new(42));
+        for _ in 0..10 {
+            assert_eq!(x.inject(&mut a, model), y.inject(&mut b, model));
+        }
+        assert_eq!(a, b);
+    }
+    let burst = FaultInjector::new(7).inject(&mut [0u8; 8], &FaultModel::Burst(4));
+    assert_eq!(burst.flipped[3] - burst.flipped[0], 3);
+    let mut buffer = [0b1010_0000u8, 0];
+    let stuck = FaultInjector::new(0).inject(
+        &mut buffer,
+        &FaultModel::StuckAt {
+            mask: vec![0xf0, 0x01],
+            value: true,
+        },
+    );
+    assert_eq!(stuck.flipped, vec![0, 12, 14]);
+    assert_eq!(buffer, [0xf0, 0x01]);
+}
+
+#[test]
+fn injected_against_detected() {
+    let secded = SecDed64::new(57);
+    let mut injector = FaultInjector::new(0x5ec_ded);
+    let mut encode_buffer = [0, 0, 0, 0, 5, 0, 0, 0];
+    secded.encode(&mut encode_buffer);
+    for _ in 0..1000 {
+        let mut buffer = encode_buffer;
+        let injection = injector.inject_codeword(&secded, &mut buffer, &FaultModel::single());
+        match secded.decode_with_correction(&mut buffer) {
+            Ok(Correction::Bit { bit, .. }) => assert_eq!(vec![bit], injection.flipped),
+            other => panic!("{:?} was reported for {:?}", other, injection),
+        }
+        let mut buffer = encode_buffer;
+        injector.inject_codeword(&secded, &mut buffer, &FaultModel::double());
+        assert!(secded.decode(&mut buffer).is_err());
+    }
+}
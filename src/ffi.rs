@@ -2,88 +2,935 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use crate::{SecDed128, SecDed64, SecDedCodec};
+//! The C API: every codec is handed to C as an opaque handle allocated by its `_new` function and released
+//! by its `_free` function, and every operation reports its outcome as a `SECDED_STATUS`.
+//!
+//! No panic may unwind into C: every exported function catches them, and reports them like invalid
+//! arguments, through its return value and `SECDED_last_error()`.
+//!
+//! `secded.h` declares this API for C, and is maintained by hand: the `header_declares_the_api` test checks
+//! that it declares every constant, type and `extern "C"` function below.
 
-// }
+use crate::frame::BlockLayout;
+use crate::{nth_bit_from_right, Correction, SecDed128, SecDed64, SecDedCodec, Uncorrectable};
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// The outcome of an operation.
+pub type SECDED_STATUS = i32;
+
+/// No error was found.
+pub const SECDED_CLEAN: SECDED_STATUS = 0;
+/// A single error was found and corrected.
+pub const SECDED_CORRECTED: SECDED_STATUS = 1;
+/// At least 2 errors were detected: the data is left untouched.
+pub const SECDED_UNCORRECTABLE: SECDED_STATUS = 2;
+/// A pointer was NULL, the size didn't match the codec, or the data to encode had bits set outside of its
+/// payload: nothing was done.
+pub const SECDED_INVALID_ARGUMENT: SECDED_STATUS = 3;
+/// The library panicked, and the data may have been partially modified.
+pub const SECDED_INTERNAL_ERROR: SECDED_STATUS = 4;
+
+/// The outcome of a decoding.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SECDED_CORRECTION {
+    /// The codec's syndrome, 0 if no error was found.
+    pub syndrome: u64,
+    /// The index of the corrected bit, counted from the right-most bit of the slice, if `status` is
+    /// `SECDED_CORRECTED`.
+    pub bit: u32,
+    pub status: SECDED_STATUS,
+}
+
+const _: () = assert!(std::mem::size_of::<SECDED_STATUS>() == 4);
+const _: () = assert!(std::mem::size_of::<SECDED_CORRECTION>() == 16);
+const _: () = assert!(std::mem::align_of::<SECDED_CORRECTION>() == std::mem::align_of::<u64>());
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(message));
+}
+
+/// Runs `f` without letting any panic unwind into C: `invalid` is returned if `f` rejects its arguments,
+/// and `panicked` if it panics, the reason becoming the thread's last error in both cases.
+fn guarded<T>(invalid: T, panicked: T, f: impl FnOnce() -> Result<T, String>) -> T {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => value,
+        Ok(Err(message)) => {
+            set_last_error(message);
+            invalid
+        }
+        Err(payload) => {
+            let message = match payload.downcast::<String>() {
+                Ok(message) => *message,
+                Err(payload) => match payload.downcast::<&str>() {
+                    Ok(message) => message.to_string(),
+                    Err(_) => "unknown panic".into(),
+                },
+            };
+            set_last_error(format!("panicked: {}", message));
+            panicked
+        }
+    }
+}
+
+fn status(f: impl FnOnce() -> Result<SECDED_STATUS, String>) -> SECDED_STATUS {
+    guarded(SECDED_INVALID_ARGUMENT, SECDED_INTERNAL_ERROR, f)
+}
+
+fn new<C>(f: impl FnOnce() -> Result<C, String>) -> *mut C {
+    let null = std::ptr::null_mut();
+    guarded(null, null, || {
+        f().map(|codec| Box::into_raw(Box::new(codec)))
+    })
+}
+
+unsafe fn codec<'a, C>(handle: *const C) -> Result<&'a C, String> {
+    handle.as_ref().ok_or_else(|| "secded is NULL".into())
+}
+
+unsafe fn slice<'a>(data: *mut u8, size: usize, name: &str) -> Result<&'a mut [u8], String> {
+    if data.is_null() {
+        return Err(format!("{} is NULL", name));
+    }
+    Ok(std::slice::from_raw_parts_mut(data, size))
+}
+
+/// Returns the size of the codewords `codec` works on, checking that `size` is one.
+fn check_size<C: SecDedCodec>(codec: &C, size: usize) -> Result<usize, String> {
+    let total_size = codec.encodable_size() + codec.code_size();
+    match codec.expected_slice_size() {
+        Some(expected) if size != expected => Err(format!(
+            "this codec works on {} bytes slices, not {}",
+            expected, size
+        )),
+        None if size < (total_size + 7) / 8 => Err(format!(
+            "this codec needs {} bits, which {} bytes can't hold",
+            total_size, size
+        )),
+        _ => Ok(size),
+    }
+}
+
+/// Returns the size of `count` slices of `size` bytes, checking that it fits in a `usize`.
+fn batch_size(size: usize, count: usize) -> Result<usize, String> {
+    size.checked_mul(count).ok_or_else(|| {
+        format!(
+            "{} slices of {} bytes overflow the address space",
+            count, size
+        )
+    })
+}
+
+/// Checks that `codeword` only has bits set in its payload.
+fn check_encodable<C: SecDedCodec>(codec: &C, codeword: &[u8]) -> Result<(), String> {
+    let total_size = codec.encodable_size() + codec.code_size();
+    match (0..codec.code_size())
+        .chain(total_size..codeword.len() * 8)
+        .find(|&bit| nth_bit_from_right(codeword, bit) == 1)
+    {
+        Some(bit) => Err(format!("bit {} is set outside of the payload", bit)),
+        None => Ok(()),
+    }
+}
+
+fn correction(result: Result<Correction, Uncorrectable>) -> SECDED_CORRECTION {
+    let (status, bit, syndrome) = match result {
+        Ok(Correction::None) => (SECDED_CLEAN, 0, 0),
+        Ok(Correction::Bit { bit, syndrome }) => (SECDED_CORRECTED, bit as u32, syndrome),
+        // Only the other codecs of this crate report more than a bit.
+        Ok(correction) => (SECDED_CORRECTED, correction.bits()[0] as u32, 0),
+        Err(Uncorrectable { syndrome }) => (SECDED_UNCORRECTABLE, 0, syndrome),
+    };
+    SECDED_CORRECTION {
+        syndrome,
+        bit,
+        status,
+    }
+}
+
+unsafe fn size_of<C: SecDedCodec>(handle: *const C, f: impl FnOnce(&C) -> usize) -> usize {
+    guarded(0, 0, || Ok(f(codec(handle)?)))
+}
+
+unsafe fn encode<C: SecDedCodec>(
+    handle: *const C,
+    data: *mut u8,
+    size: usize,
+    count: usize,
+) -> SECDED_STATUS {
+    status(|| {
+        let codec = codec(handle)?;
+        let size = check_size(codec, size)?;
+        let data = slice(data, batch_size(size, count)?, "data")?;
+        for (i, codeword) in data.chunks(size).enumerate() {
+            check_encodable(codec, codeword).map_err(|error| match count {
+                1 => error,
+                _ => format!("codeword {}: {}", i, error),
+            })?;
+        }
+        for codeword in data.chunks_mut(size) {
+            codec.encode(codeword);
+        }
+        Ok(SECDED_CLEAN)
+    })
+}
+
+/// Decodes `count` codewords, returning the worst status among them.
+unsafe fn decode<C: SecDedCodec>(
+    handle: *const C,
+    data: *mut u8,
+    size: usize,
+    count: usize,
+    corrections: *mut SECDED_CORRECTION,
+) -> SECDED_STATUS {
+    status(|| {
+        let codec = codec(handle)?;
+        let size = check_size(codec, size)?;
+        let data = slice(data, batch_size(size, count)?, "data")?;
+        let mut worst = SECDED_CLEAN;
+        for (i, codeword) in data.chunks_mut(size).enumerate() {
+            let correction = correction(codec.decode_with_correction(codeword));
+            worst = worst.max(correction.status);
+            if !corrections.is_null() {
+                *corrections.add(i) = correction;
+            }
+        }
+        Ok(worst)
+    })
+}
+
+/// Returns the size of the blocks `length` bytes are split into, checking that it fits in a `usize`.
+fn blocks_size(layout: &BlockLayout, length: usize) -> Result<usize, String> {
+    layout
+        .block_count(length)
+        .checked_mul(layout.word_size)
+        .ok_or_else(|| format!("the blocks of {} bytes overflow the address space", length))
+}
+
+fn layout<C: SecDedCodec>(codec: &C) -> Result<BlockLayout, String> {
+    let layout = BlockLayout::of(codec);
+    if layout.payload_size == 0 {
+        return Err("this codec can't carry a byte of payload per block".into());
+    }
+    Ok(layout)
+}
+
+unsafe fn encode_blocks<C: SecDedCodec>(
+    handle: *const C,
+    data: *const u8,
+    length: usize,
+    blocks: *mut u8,
+    size: usize,
+) -> SECDED_STATUS {
+    status(|| {
+        let codec = codec(handle)?;
+        let layout = layout(codec)?;
+        let expected = blocks_size(&layout, length)?;
+        if size != expected {
+            return Err(format!(
+                "{} bytes take {} bytes of blocks, not {}",
+                length, expected, size
+            ));
+        }
+        let data = slice(data as *mut u8, length, "data")?;
+        let blocks = slice(blocks, size, "blocks")?;
+        for (chunk, block) in data
+            .chunks(layout.payload_size)
+            .zip(blocks.chunks_mut(layout.word_size))
+        {
+            for x in block.iter_mut() {
+                *x = 0;
+            }
+            block[layout.payload_offset..layout.payload_offset + chunk.len()]
+                .copy_from_slice(chunk);
+            codec.encode(block);
+        }
+        Ok(SECDED_CLEAN)
+    })
+}
+
+/// Decodes blocks without modifying them, returning the worst status among them.
+unsafe fn decode_blocks<C: SecDedCodec>(
+    handle: *const C,
+    blocks: *const u8,
+    size: usize,
+    data: *mut u8,
+    length: usize,
+) -> SECDED_STATUS {
+    status(|| {
+        let codec = codec(handle)?;
+        let layout = layout(codec)?;
+        let expected = blocks_size(&layout, length)?;
+        if size != expected {
+            return Err(format!(
+                "{} bytes take {} bytes of blocks, not {}",
+                length, expected, size
+            ));
+        }
+        let blocks = slice(blocks as *mut u8, size, "blocks")?;
+        let data = slice(data, length, "data")?;
+        let mut buffer = vec![0u8; layout.word_size];
+        let mut worst = SECDED_CLEAN;
+        for (chunk, block) in data
+            .chunks_mut(layout.payload_size)
+            .zip(blocks.chunks(layout.word_size))
+        {
+            buffer.copy_from_slice(block);
+            worst = worst.max(correction(codec.decode_with_correction(&mut buffer)).status);
+            chunk.copy_from_slice(
+                &buffer[layout.payload_offset..layout.payload_offset + chunk.len()],
+            );
+        }
+        Ok(worst)
+    })
+}
+
+unsafe fn free<C>(handle: *mut C) {
+    guarded((), (), || {
+        if !handle.is_null() {
+            drop(Box::from_raw(handle));
+        }
+        Ok(())
+    })
+}
+
+/// Returns the reason of the last failure on the calling thread, or NULL if nothing failed yet.
+/// The string remains valid until the next failure on the same thread.
 #[no_mangle]
-pub unsafe fn SECDED_64_new(encodable_size: usize) -> SecDed64 {
-    crate::SecDed64::new(encodable_size)
+pub extern "C" fn SECDED_last_error() -> *const c_char {
+    LAST_ERROR.with(|last_error| match &*last_error.borrow() {
+        Some(message) => message.as_ptr(),
+        None => std::ptr::null(),
+    })
 }
 
+/// A `SecDed64` codec, working on 8 bytes slices.
+pub type SECDED_64 = SecDed64;
+
+/// Returns a handle to a `SecDed64` codec, or NULL if `encodable_size > 57`.
+/// The handle must be released with `SECDED_64_free()`.
 #[no_mangle]
-pub unsafe fn SECDED_64_encode(secded: *const SecDed64, data: *mut [u8; 8]) {
-    (*secded).encode(&mut *data);
+pub extern "C" fn SECDED_64_new(encodable_size: usize) -> *mut SECDED_64 {
+    new(|| match encodable_size {
+        0..=57 => Ok(SecDed64::new(encodable_size)),
+        _ => Err(format!("SecDed64 can't encode {} bits", encodable_size)),
+    })
 }
 
+/// Releases `secded`, which may be NULL.
 #[no_mangle]
-pub unsafe fn SECDED_64_decode(secded: *const SecDed64, data: *mut [u8; 8]) -> bool {
-    match (*secded).decode(&mut *data) {
-        Err(()) => false,
-        Ok(()) => true,
-    }
+pub unsafe extern "C" fn SECDED_64_free(secded: *mut SECDED_64) {
+    free(secded)
 }
 
+/// Returns the number of bits `secded` encodes, or 0 if it is NULL.
 #[no_mangle]
-pub unsafe fn SECDED_128_new(encodable_size: usize) -> SecDed128 {
-    crate::SecDed128::new(encodable_size)
+pub unsafe extern "C" fn SECDED_64_encodable_size(secded: *const SECDED_64) -> usize {
+    size_of(secded, |codec| codec.encodable_size())
 }
 
+/// Returns the number of bits of code `secded` adds to the payload, or 0 if it is NULL.
 #[no_mangle]
-pub unsafe fn SECDED_128_encode(secded: *const SecDed128, data: *mut [u8; 16]) {
-    (*secded).encode(&mut *data);
+pub unsafe extern "C" fn SECDED_64_code_size(secded: *const SECDED_64) -> usize {
+    size_of(secded, |codec| codec.code_size())
 }
 
+/// Returns the size in bytes of the smallest slice `secded` works on, or 0 if it is NULL.
 #[no_mangle]
-pub unsafe fn SECDED_128_decode(secded: *const SecDed128, data: *mut [u8; 16]) -> bool {
-    match (*secded).decode(&mut *data) {
-        Err(()) => false,
-        Ok(()) => true,
-    }
+pub unsafe extern "C" fn SECDED_64_slice_size(secded: *const SECDED_64) -> usize {
+    size_of(secded, |codec| BlockLayout::of(codec).word_size)
 }
 
-#[test]
-fn hamming_both() {
-    unsafe {
-        let secded = SECDED_64_new(57);
-        let expected = [0, 0, 0, 0, 5, 0, 0];
-        let mut buffer = [0u8; 8];
-        buffer[..7].clone_from_slice(&expected);
-        SECDED_64_encode(&secded, buffer.as_mut_ptr() as *mut [u8; 8]);
-        assert!(SECDED_64_decode(
-            &secded,
-            buffer.as_mut_ptr() as *mut [u8; 8]
-        ));
-        assert_eq!(expected, buffer[..7]);
-    }
+/// Encodes `data[..size]` in place. The code bits must be set to 0.
+#[no_mangle]
+pub unsafe extern "C" fn SECDED_64_encode(
+    secded: *const SECDED_64,
+    data: *mut u8,
+    size: usize,
+) -> SECDED_STATUS {
+    encode(secded, data, size, 1)
+}
+
+/// Decodes `data[..size]` in place, and resets the code bits to 0.
+#[no_mangle]
+pub unsafe extern "C" fn SECDED_64_decode(
+    secded: *const SECDED_64,
+    data: *mut u8,
+    size: usize,
+) -> SECDED_STATUS {
+    decode(secded, data, size, 1, std::ptr::null_mut())
+}
+
+/// Decodes `data[..size]` in place like `SECDED_64_decode()`, and describes the outcome in `correction`,
+/// which may be NULL.
+#[no_mangle]
+pub unsafe extern "C" fn SECDED_64_decode_with_correction(
+    secded: *const SECDED_64,
+    data: *mut u8,
+    size: usize,
+    correction: *mut SECDED_CORRECTION,
+) -> SECDED_STATUS {
+    decode(secded, data, size, 1, correction)
+}
+
+/// Encodes the `count` codewords of `size` bytes stored contiguously in `data`. Nothing is encoded if any
+/// of them has bits set outside of its payload.
+#[no_mangle]
+pub unsafe extern "C" fn SECDED_64_encode_batch(
+    secded: *const SECDED_64,
+    data: *mut u8,
+    size: usize,
+    count: usize,
+) -> SECDED_STATUS {
+    encode(secded, data, size, count)
+}
+
+/// Decodes the `count` codewords of `size` bytes stored contiguously in `data`, and returns the worst
+/// status among them. Unless it is NULL, `corrections[i]` describes the outcome for codeword `i`.
+#[no_mangle]
+pub unsafe extern "C" fn SECDED_64_decode_batch(
+    secded: *const SECDED_64,
+    data: *mut u8,
+    size: usize,
+    count: usize,
+    corrections: *mut SECDED_CORRECTION,
+) -> SECDED_STATUS {
+    decode(secded, data, size, count, corrections)
+}
+
+/// Returns the size in bytes of the blocks `SECDED_64_encode_blocks()` splits `length` bytes into, or 0 if
+/// `secded` is NULL, can't carry a byte of payload per block, or if that size overflows.
+#[no_mangle]
+pub unsafe extern "C" fn SECDED_64_encoded_size(secded: *const SECDED_64, length: usize) -> usize {
+    guarded(0, 0, || {
+        let layout = layout(codec(secded)?)?;
+        blocks_size(&layout, length)
+    })
+}
+
+/// Splits `data[..length]` into blocks carrying as many whole bytes of payload as `secded` can encode,
+/// and encodes them into `blocks[..size]`, `size` being `SECDED_64_encoded_size(secded, length)`.
+#[no_mangle]
+pub unsafe extern "C" fn SECDED_64_encode_blocks(
+    secded: *const SECDED_64,
+    data: *const u8,
+    length: usize,
+    blocks: *mut u8,
+    size: usize,
+) -> SECDED_STATUS {
+    encode_blocks(secded, data, length, blocks, size)
+}
+
+/// Decodes `blocks[..size]`, produced by `SECDED_64_encode_blocks()`, into `data[..length]`, and returns
+/// the worst status among the blocks. The blocks are left untouched.
+#[no_mangle]
+pub unsafe extern "C" fn SECDED_64_decode_blocks(
+    secded: *const SECDED_64,
+    blocks: *const u8,
+    size: usize,
+    data: *mut u8,
+    length: usize,
+) -> SECDED_STATUS {
+    decode_blocks(secded, blocks, size, data, length)
+}
+
+/// A `SecDed128` codec, working on 16 bytes slices.
+pub type SECDED_128 = SecDed128;
+
+/// Returns a handle to a `SecDed128` codec, or NULL if `encodable_size > 120`.
+/// The handle must be released with `SECDED_128_free()`.
+#[no_mangle]
+pub extern "C" fn SECDED_128_new(encodable_size: usize) -> *mut SECDED_128 {
+    new(|| match encodable_size {
+        0..=120 => Ok(SecDed128::new(encodable_size)),
+        _ => Err(format!("SecDed128 can't encode {} bits", encodable_size)),
+    })
+}
+
+/// Releases `secded`, which may be NULL.
+#[no_mangle]
+pub unsafe extern "C" fn SECDED_128_free(secded: *mut SECDED_128) {
+    free(secded)
+}
+
+/// Returns the number of bits `secded` encodes, or 0 if it is NULL.
+#[no_mangle]
+pub unsafe extern "C" fn SECDED_128_encodable_size(secded: *const SECDED_128) -> usize {
+    size_of(secded, |codec| codec.encodable_size())
+}
+
+/// Returns the number of bits of code `secded` adds to the payload, or 0 if it is NULL.
+#[no_mangle]
+pub unsafe extern "C" fn SECDED_128_code_size(secded: *const SECDED_128) -> usize {
+    size_of(secded, |codec| codec.code_size())
+}
+
+/// Returns the size in bytes of the smallest slice `secded` works on, or 0 if it is NULL.
+#[no_mangle]
+pub unsafe extern "C" fn SECDED_128_slice_size(secded: *const SECDED_128) -> usize {
+    size_of(secded, |codec| BlockLayout::of(codec).word_size)
+}
+
+/// Encodes `data[..size]` in place. The code bits must be set to 0.
+#[no_mangle]
+pub unsafe extern "C" fn SECDED_128_encode(
+    secded: *const SECDED_128,
+    data: *mut u8,
+    size: usize,
+) -> SECDED_STATUS {
+    encode(secded, data, size, 1)
+}
+
+/// Decodes `data[..size]` in place, and resets the code bits to 0.
+#[no_mangle]
+pub unsafe extern "C" fn SECDED_128_decode(
+    secded: *const SECDED_128,
+    data: *mut u8,
+    size: usize,
+) -> SECDED_STATUS {
+    decode(secded, data, size, 1, std::ptr::null_mut())
+}
+
+/// Decodes `data[..size]` in place like `SECDED_128_decode()`, and describes the outcome in `correction`,
+/// which may be NULL.
+#[no_mangle]
+pub unsafe extern "C" fn SECDED_128_decode_with_correction(
+    secded: *const SECDED_128,
+    data: *mut u8,
+    size: usize,
+    correction: *mut SECDED_CORRECTION,
+) -> SECDED_STATUS {
+    decode(secded, data, size, 1, correction)
+}
+
+/// Encodes the `count` codewords of `size` bytes stored contiguously in `data`. Nothing is encoded if any
+/// of them has bits set outside of its payload.
+#[no_mangle]
+pub unsafe extern "C" fn SECDED_128_encode_batch(
+    secded: *const SECDED_128,
+    data: *mut u8,
+    size: usize,
+    count: usize,
+) -> SECDED_STATUS {
+    encode(secded, data, size, count)
+}
+
+/// Decodes the `count` codewords of `size` bytes stored contiguously in `data`, and returns the worst
+/// status among them. Unless it is NULL, `corrections[i]` describes the outcome for codeword `i`.
+#[no_mangle]
+pub unsafe extern "C" fn SECDED_128_decode_batch(
+    secded: *const SECDED_128,
+    data: *mut u8,
+    size: usize,
+    count: usize,
+    corrections: *mut SECDED_CORRECTION,
+) -> SECDED_STATUS {
+    decode(secded, data, size, count, corrections)
+}
+
+/// Returns the size in bytes of the blocks `SECDED_128_encode_blocks()` splits `length` bytes into, or 0 if
+/// `secded` is NULL, can't carry a byte of payload per block, or if that size overflows.
+#[no_mangle]
+pub unsafe extern "C" fn SECDED_128_encoded_size(
+    secded: *const SECDED_128,
+    length: usize,
+) -> usize {
+    guarded(0, 0, || {
+        let layout = layout(codec(secded)?)?;
+        blocks_size(&layout, length)
+    })
+}
+
+/// Splits `data[..length]` into blocks carrying as many whole bytes of payload as `secded` can encode,
+/// and encodes them into `blocks[..size]`, `size` being `SECDED_128_encoded_size(secded, length)`.
+#[no_mangle]
+pub unsafe extern "C" fn SECDED_128_encode_blocks(
+    secded: *const SECDED_128,
+    data: *const u8,
+    length: usize,
+    blocks: *mut u8,
+    size: usize,
+) -> SECDED_STATUS {
+    encode_blocks(secded, data, length, blocks, size)
+}
+
+/// Decodes `blocks[..size]`, produced by `SECDED_128_encode_blocks()`, into `data[..length]`, and returns
+/// the worst status among the blocks. The blocks are left untouched.
+#[no_mangle]
+pub unsafe extern "C" fn SECDED_128_decode_blocks(
+    secded: *const SECDED_128,
+    blocks: *const u8,
+    size: usize,
+    data: *mut u8,
+    length: usize,
+) -> SECDED_STATUS {
+    decode_blocks(secded, blocks, size, data, length)
 }
 
 #[cfg(feature = "dyn")]
 mod dynamic {
-    use crate::SecDedCodec;
-    #[repr(C)]
-    pub struct SECDED_DYN {}
+    use super::*;
+    use crate::SecDedDynamic;
+
+    /// A `SecDedDynamic` codec, working on slices of any size that can hold its payload and code.
+    pub type SECDED_DYN = SecDedDynamic;
+
+    /// Returns a handle to a `SecDedDynamic` codec, or NULL if it couldn't be built.
+    /// The handle must be released with `SECDED_DYN_free()`.
+    #[no_mangle]
+    pub extern "C" fn SECDED_DYN_new(encodable_size: usize) -> *mut SECDED_DYN {
+        new(|| Ok(SecDedDynamic::new(encodable_size)))
+    }
+
+    /// Releases `secded`, which may be NULL.
+    #[no_mangle]
+    pub unsafe extern "C" fn SECDED_DYN_free(secded: *mut SECDED_DYN) {
+        free(secded)
+    }
+
+    /// Returns the number of bits `secded` encodes, or 0 if it is NULL.
+    #[no_mangle]
+    pub unsafe extern "C" fn SECDED_DYN_encodable_size(secded: *const SECDED_DYN) -> usize {
+        size_of(secded, |codec| codec.encodable_size())
+    }
+
+    /// Returns the number of bits of code `secded` adds to the payload, or 0 if it is NULL.
+    #[no_mangle]
+    pub unsafe extern "C" fn SECDED_DYN_code_size(secded: *const SECDED_DYN) -> usize {
+        size_of(secded, |codec| codec.code_size())
+    }
+
+    /// Returns the size in bytes of the smallest slice `secded` works on, or 0 if it is NULL.
+    #[no_mangle]
+    pub unsafe extern "C" fn SECDED_DYN_slice_size(secded: *const SECDED_DYN) -> usize {
+        size_of(secded, |codec| BlockLayout::of(codec).word_size)
+    }
+
+    /// Encodes `data[..size]` in place. The code bits must be set to 0.
+    #[no_mangle]
+    pub unsafe extern "C" fn SECDED_DYN_encode(
+        secded: *const SECDED_DYN,
+        data: *mut u8,
+        size: usize,
+    ) -> SECDED_STATUS {
+        encode(secded, data, size, 1)
+    }
+
+    /// Decodes `data[..size]` in place, and resets the code bits to 0.
+    #[no_mangle]
+    pub unsafe extern "C" fn SECDED_DYN_decode(
+        secded: *const SECDED_DYN,
+        data: *mut u8,
+        size: usize,
+    ) -> SECDED_STATUS {
+        decode(secded, data, size, 1, std::ptr::null_mut())
+    }
+
+    /// Decodes `data[..size]` in place like `SECDED_DYN_decode()`, and describes the outcome in `correction`,
+    /// which may be NULL.
+    #[no_mangle]
+    pub unsafe extern "C" fn SECDED_DYN_decode_with_correction(
+        secded: *const SECDED_DYN,
+        data: *mut u8,
+        size: usize,
+        correction: *mut SECDED_CORRECTION,
+    ) -> SECDED_STATUS {
+        decode(secded, data, size, 1, correction)
+    }
+
+    /// Encodes the `count` codewords of `size` bytes stored contiguously in `data`. Nothing is encoded if any
+    /// of them has bits set outside of its payload.
+    #[no_mangle]
+    pub unsafe extern "C" fn SECDED_DYN_encode_batch(
+        secded: *const SECDED_DYN,
+        data: *mut u8,
+        size: usize,
+        count: usize,
+    ) -> SECDED_STATUS {
+        encode(secded, data, size, count)
+    }
 
+    /// Decodes the `count` codewords of `size` bytes stored contiguously in `data`, and returns the worst
+    /// status among them. Unless it is NULL, `corrections[i]` describes the outcome for codeword `i`.
     #[no_mangle]
-    pub unsafe fn SECDED_DYN_new(encodable_size: usize) -> *const SECDED_DYN {
-        Box::into_raw(Box::new(crate::SecDedDynamic::new(encodable_size))) as *const SECDED_DYN
+    pub unsafe extern "C" fn SECDED_DYN_decode_batch(
+        secded: *const SECDED_DYN,
+        data: *mut u8,
+        size: usize,
+        count: usize,
+        corrections: *mut SECDED_CORRECTION,
+    ) -> SECDED_STATUS {
+        decode(secded, data, size, count, corrections)
     }
+
+    /// Returns the size in bytes of the blocks `SECDED_DYN_encode_blocks()` splits `length` bytes into, or 0 if
+    /// `secded` is NULL, can't carry a byte of payload per block, or if that size overflows.
     #[no_mangle]
-    pub unsafe fn SECDED_DYN_free(secded: *const SECDED_DYN) {
-        Box::from_raw(secded as *mut crate::SecDedDynamic);
+    pub unsafe extern "C" fn SECDED_DYN_encoded_size(
+        secded: *const SECDED_DYN,
+        length: usize,
+    ) -> usize {
+        guarded(0, 0, || {
+            let layout = layout(codec(secded)?)?;
+            blocks_size(&layout, length)
+        })
     }
 
+    /// Splits `data[..length]` into blocks carrying as many whole bytes of payload as `secded` can encode,
+    /// and encodes them into `blocks[..size]`, `size` being `SECDED_DYN_encoded_size(secded, length)`.
     #[no_mangle]
-    pub unsafe fn SECDED_DYN_encode(secded: *const SECDED_DYN, data: *mut u8, size: usize) {
-        let slice = std::slice::from_raw_parts_mut(data, size);
-        (*(secded as *const crate::SecDedDynamic)).encode(slice);
+    pub unsafe extern "C" fn SECDED_DYN_encode_blocks(
+        secded: *const SECDED_DYN,
+        data: *const u8,
+        length: usize,
+        blocks: *mut u8,
+        size: usize,
+    ) -> SECDED_STATUS {
+        encode_blocks(secded, data, length, blocks, size)
     }
 
+    /// Decodes `blocks[..size]`, produced by `SECDED_DYN_encode_blocks()`, into `data[..length]`, and returns
+    /// the worst status among the blocks. The blocks are left untouched.
     #[no_mangle]
-    pub unsafe fn SECDED_DYN_decode(secded: *const SECDED_DYN, data: *mut u8, size: usize) -> bool {
-        let slice = std::slice::from_raw_parts_mut(data, size);
-        match (*(secded as *const crate::SecDedDynamic)).decode(slice) {
-            Err(()) => false,
-            Ok(()) => true,
+    pub unsafe extern "C" fn SECDED_DYN_decode_blocks(
+        secded: *const SECDED_DYN,
+        blocks: *const u8,
+        size: usize,
+        data: *mut u8,
+        length: usize,
+    ) -> SECDED_STATUS {
+        decode_blocks(secded, blocks, size, data, length)
+    }
+}
+
+#[test]
+fn header_declares_the_api() {
+    let header = include_str!("../secded.h");
+    let declarations: Vec<&str> = header
+        .lines()
+        .filter(|line| !line.starts_with("//"))
+        .collect();
+    let declared = |what: &str, found: &dyn Fn(&str) -> bool| {
+        assert!(
+            declarations.iter().any(|line| found(line)),
+            "secded.h doesn't declare {}",
+            what
+        )
+    };
+    for line in include_str!("ffi.rs")
+        .lines()
+        .map(str::trim)
+        .take_while(|&line| line != "#[test]")
+    {
+        if let Some(constant) = line.strip_prefix("pub const ") {
+            let name = &constant[..constant.find(':').unwrap()];
+            let value = constant
+                .split('=')
+                .nth(1)
+                .unwrap()
+                .trim_end_matches(';')
+                .trim();
+            let define = format!("#define {} {}", name, value);
+            declared(&define, &|line| line == define);
+        } else if let Some(name) = line.strip_prefix("pub struct ") {
+            let typedef = format!("typedef struct {} {{", name.trim_end_matches('{').trim());
+            declared(&typedef, &|line| line == typedef);
+        } else if let Some(alias) = line.strip_prefix("pub type ") {
+            let name = format!(" {};", alias[..alias.find('=').unwrap()].trim());
+            declared(&name, &|line| {
+                line.starts_with("typedef ") && line.ends_with(&name)
+            });
+        } else if let Some(index) = line.find("extern \"C\" fn ") {
+            let signature = &line[index + 14..];
+            let name = &signature[..signature.find('(').unwrap()];
+            let call = format!("{}(", name);
+            declared(name, &|line| {
+                line.contains(&format!(" {}", call)) || line.contains(&format!("*{}", call))
+            });
         }
     }
 }
+
+#[test]
+fn hamming_both() {
+    unsafe {
+        let secded = SECDED_64_new(57);
+        let expected = [0, 0, 0, 0, 5, 0, 0, 0];
+        let mut buffer = expected;
+        assert_eq!(
+            SECDED_64_encode(secded, buffer.as_mut_ptr(), 8),
+            SECDED_CLEAN
+        );
+        assert_eq!(
+            SECDED_64_decode(secded, buffer.as_mut_ptr(), 8),
+            SECDED_CLEAN
+        );
+        assert_eq!(buffer, expected);
+        SECDED_64_encode(secded, buffer.as_mut_ptr(), 8);
+        buffer[7] ^= 1 << 1;
+        assert_eq!(
+            SECDED_64_decode(secded, buffer.as_mut_ptr(), 8),
+            SECDED_CORRECTED
+        );
+        assert_eq!(buffer, expected);
+        SECDED_64_encode(secded, buffer.as_mut_ptr(), 8);
+        buffer[0] ^= 3;
+        assert_eq!(
+            SECDED_64_decode(secded, buffer.as_mut_ptr(), 8),
+            SECDED_UNCORRECTABLE
+        );
+        SECDED_64_free(secded);
+        let secded = SECDED_128_new(57);
+        let mut buffer = [0u8; 16];
+        buffer[12] = 5;
+        SECDED_128_encode(secded, buffer.as_mut_ptr(), 16);
+        buffer[9] ^= 1;
+        assert_eq!(
+            SECDED_128_decode(secded, buffer.as_mut_ptr(), 16),
+            SECDED_CORRECTED
+        );
+        assert_eq!(buffer[12], 5);
+        SECDED_128_free(secded);
+    }
+}
+
+#[test]
+fn invalid_arguments() {
+    assert!(SECDED_64_new(58).is_null());
+    assert!(SECDED_128_new(121).is_null());
+    unsafe {
+        let secded = SECDED_64_new(57);
+        let mut buffer = [0u8; 16];
+        assert_eq!(
+            SECDED_64_encode(secded, buffer.as_mut_ptr(), 16),
+            SECDED_INVALID_ARGUMENT
+        );
+        assert_eq!(
+            SECDED_64_decode(secded, buffer.as_mut_ptr(), 7),
+            SECDED_INVALID_ARGUMENT
+        );
+        assert_eq!(
+            SECDED_64_decode(std::ptr::null(), buffer.as_mut_ptr(), 8),
+            SECDED_INVALID_ARGUMENT
+        );
+        assert_eq!(
+            SECDED_64_decode(secded, std::ptr::null_mut(), 8),
+            SECDED_INVALID_ARGUMENT
+        );
+        // The code bits must be cleared before encoding.
+        buffer[7] = 1;
+        assert_eq!(
+            SECDED_64_encode(secded, buffer.as_mut_ptr(), 8),
+            SECDED_INVALID_ARGUMENT
+        );
+        SECDED_64_free(secded);
+        SECDED_64_free(std::ptr::null_mut());
+    }
+}
+
+#[test]
+fn last_error() {
+    let message = || unsafe { std::ffi::CStr::from_ptr(SECDED_last_error()) };
+    assert!(SECDED_64_new(100).is_null());
+    assert_eq!(message().to_str(), Ok("SecDed64 can't encode 100 bits"));
+    let mut buffer = [0xffu8; 8];
+    let secded = SECDED_64_new(57);
+    unsafe {
+        let status = SECDED_64_encode(secded, buffer.as_mut_ptr(), 8);
+        assert_eq!(status, SECDED_INVALID_ARGUMENT);
+        SECDED_64_free(secded);
+    }
+    assert_eq!(
+        message().to_str(),
+        Ok("bit 0 is set outside of the payload")
+    );
+    assert_eq!(status(|| panic!("boom")), SECDED_INTERNAL_ERROR);
+    assert_eq!(message().to_str(), Ok("panicked: boom"));
+    // Each thread has its own last error.
+    std::thread::spawn(|| assert!(SECDED_last_error().is_null()))
+        .join()
+        .unwrap();
+}
+
+#[test]
+fn batches_and_blocks() {
+    unsafe {
+        let secded = SECDED_128_new(100);
+        assert_eq!(
+            (
+                SECDED_128_encodable_size(secded),
+                SECDED_128_code_size(secded),
+                SECDED_128_slice_size(secded)
+            ),
+            (100, 8, 16)
+        );
+        let mut words = [0u8; 48];
+        for (i, x) in words.iter_mut().enumerate() {
+            if i % 16 >= 3 && i % 16 != 15 {
+                *x = i as u8;
+            }
+        }
+        let expected = words;
+        assert_eq!(
+            SECDED_128_encode_batch(secded, words.as_mut_ptr(), 16, 3),
+            SECDED_CLEAN
+        );
+        words[16 + 15] ^= 1 << 5;
+        words[32] ^= 1 << 3;
+        words[35] ^= 1;
+        let mut corrections = [correction(Ok(Correction::None)); 3];
+        let status =
+            SECDED_128_decode_batch(secded, words.as_mut_ptr(), 16, 3, corrections.as_mut_ptr());
+        assert_eq!(status, SECDED_UNCORRECTABLE);
+        let outcomes: Vec<_> = corrections.iter().map(|c| (c.status, c.bit)).collect();
+        assert_eq!(
+            outcomes,
+            [
+                (SECDED_CLEAN, 0),
+                (SECDED_CORRECTED, 5),
+                (SECDED_UNCORRECTABLE, 0)
+            ]
+        );
+        assert_eq!(words[..32], expected[..32]);
+        let status = SECDED_128_decode_batch(
+            secded,
+            words.as_mut_ptr(),
+            16,
+            usize::MAX,
+            std::ptr::null_mut(),
+        );
+        assert_eq!(status, SECDED_INVALID_ARGUMENT);
+        assert_eq!(
+            std::ffi::CStr::from_ptr(SECDED_last_error()).to_str(),
+            Ok(&*format!(
+                "{} slices of 16 bytes overflow the address space",
+                usize::MAX
+            ))
+        );
+        SECDED_128_free(secded);
+
+        let secded = SECDED_64_new(57);
+        let data: Vec<u8> = (0..20).collect();
+        let size = SECDED_64_encoded_size(secded, data.len());
+        assert_eq!(size, 24);
+        let mut blocks = vec![0u8; size];
+        let status = SECDED_64_encode_blocks(secded, data.as_ptr(), 20, blocks.as_mut_ptr(), size);
+        assert_eq!(status, SECDED_CLEAN);
+        blocks[9] ^= 0x10;
+        let mut decoded = vec![0u8; 20];
+        let status =
+            SECDED_64_decode_blocks(secded, blocks.as_ptr(), size, decoded.as_mut_ptr(), 20);
+        assert_eq!((status, &decoded), (SECDED_CORRECTED, &data));
+        let status = SECDED_64_decode_blocks(secded, blocks.as_ptr(), 16, decoded.as_mut_ptr(), 20);
+        assert_eq!(status, SECDED_INVALID_ARGUMENT);
+        SECDED_64_free(secded);
+    }
+}
@@ -0,0 +1,163 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::*;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        let mut buffer = [0; 8];
+        byteorder::BigEndian::write_u64(&mut buffer, value);
+        self.write(&buffer);
+    }
+}
+
+/// Computes the fingerprint of any codec by encoding each unit payload and hashing the resulting
+/// correction codes, which together with the sizes fully describe the `H` matrix and the bit layout.
+pub(crate) fn fingerprint<C: SecDedCodec + ?Sized>(codec: &C) -> u64 {
+    let (encodable_size, code_size) = (codec.encodable_size(), codec.code_size());
+    let word_size = frame::BlockLayout::of(codec).word_size;
+    let mut hasher = Fnv1a(FNV_OFFSET);
+    hasher.write(b"secded");
+    hasher.write_u64(encodable_size as u64);
+    hasher.write_u64(code_size as u64);
+    hasher.write_u64(word_size as u64);
+    for code in unit_codes(codec) {
+        for limb in code {
+            hasher.write_u64(limb);
+        }
+    }
+    hasher.0
+}
+
+/// Reasons for which `Fingerprinted::decode_from` may refuse a codeword.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FingerprintError {
+    /// The peer's fingerprint doesn't match the local codec's, decoding would produce garbage.
+    Mismatch { local: u64, remote: u64 },
+    /// The fingerprints matched, but the codeword contained at least 2 errors.
+    Uncorrectable,
+}
+
+/// Wraps a codec and caches its fingerprint, so that codewords coming from a peer can be checked against
+/// the fingerprint it announced during a handshake before being decoded.
+pub struct Fingerprinted<C> {
+    codec: C,
+    fingerprint: u64,
+}
+
+impl<C: SecDedCodec> Fingerprinted<C> {
+    pub fn new(codec: C) -> Self {
+        let fingerprint = codec.fingerprint();
+        Fingerprinted { codec, fingerprint }
+    }
+
+    pub fn codec(&self) -> &C {
+        &self.codec
+    }
+
+    pub fn into_inner(self) -> C {
+        self.codec
+    }
+
+    /// Returns `Ok(())` if `remote` is the fingerprint of a codec compatible with this one.
+    pub fn check(&self, remote: u64) -> Result<(), FingerprintError> {
+        if remote == self.fingerprint {
+            Ok(())
+        } else {
+            Err(FingerprintError::Mismatch {
+                local: self.fingerprint,
+                remote,
+            })
+        }
+    }
+
+    /// Decodes the data IN-PLACE, but only if `remote` matches the local fingerprint.
+    /// `data` is left untouched if the fingerprints don't match.
+    pub fn decode_from(&self, data: &mut [u8], remote: u64) -> Result<(), FingerprintError> {
+        self.check(remote)?;
+        self.codec
+            .decode(data)
+            .map_err(|()| FingerprintError::Uncorrectable)
+    }
+}
+
+impl<C: SecDedCodec> SecDedCodec for Fingerprinted<C> {
+    fn encodable_size(&self) -> usize {
+        self.codec.encodable_size()
+    }
+    fn code_size(&self) -> usize {
+        self.codec.code_size()
+    }
+    fn expected_slice_size(&self) -> Option<usize> {
+        self.codec.expected_slice_size()
+    }
+    fn fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+    fn encode(&self, data: &mut [u8]) {
+        self.codec.encode(data)
+    }
+    fn decode(&self, data: &mut [u8]) -> Result<(), ()> {
+        self.codec.decode(data)
+    }
+    fn decode_with_correction(&self, data: &mut [u8]) -> Result<Correction, Uncorrectable> {
+        self.codec.decode_with_correction(data)
+    }
+}
+
+#[test]
+fn fingerprints() {
+    assert_ne!(
+        SecDed64::new(57).fingerprint(),
+        SecDed64::new(56).fingerprint()
+    );
+    assert_ne!(
+        SecDed64::new(57).fingerprint(),
+        SecDed128::new(57).fingerprint()
+    );
+    assert_eq!(
+        SecDed64::new(57).fingerprint(),
+        SecDed64::new(57).fingerprint()
+    );
+    #[cfg(feature = "dyn")]
+    assert_eq!(
+        SecDed64::new(57).fingerprint(),
+        SecDedDynamic::new(57).fingerprint()
+    );
+}
+
+#[test]
+fn decode_from() {
+    let local = Fingerprinted::new(SecDed64::new(57));
+    let remote = SecDed64::new(56);
+    let expected = [0, 0, 0, 0, 5, 0, 0, 0];
+    let mut buffer = expected;
+    remote.encode(&mut buffer);
+    let encoded = buffer;
+    assert_eq!(
+        local.decode_from(&mut buffer, remote.fingerprint()),
+        Err(FingerprintError::Mismatch {
+            local: local.fingerprint(),
+            remote: remote.fingerprint()
+        })
+    );
+    assert_eq!(buffer, encoded);
+    buffer = expected;
+    local.encode(&mut buffer);
+    buffer[3] ^= 1 << 2;
+    assert_eq!(local.decode_from(&mut buffer, local.fingerprint()), Ok(()));
+    assert_eq!(buffer, expected);
+}
@@ -0,0 +1,492 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A self-describing container for data protected by this crate.
+//!
+//! A frame starts with a `FrameHeader`, itself protected by `SecDed64::new(57)`, which carries the codec
+//! used for the body, its fingerprint, the original length of the data and the number of blocks that follow.
+//! The body is the sequence of encoded blocks, each carrying `BlockLayout::payload_size` bytes of data.
+
+use crate::*;
+use std::convert::TryFrom;
+
+const MAGIC: [u8; 4] = *b"SDED";
+/// The version written in frame headers. It names the parity convention of the body's codewords: version 1
+/// is left to codewords whose last code bit is the parity of the rest of the code (as written by secded 1.x),
+/// and version 2 is used for those whose last code bit is the overall parity of the codeword (since 2.0.0).
+pub const FRAME_VERSION: u8 = 2;
+
+const HEADER_WORD_PAYLOAD: usize = 7;
+const HEADER_WORDS: usize = 5;
+
+/// Describes where the payload bytes sit inside a codeword, so that byte-oriented data can be split into
+/// blocks for any codec.
+///
+/// The payload is placed right above the bytes that hold the correction code, so that it only ever uses
+/// bits within the `encodable_size() + code_size()` rightmost bits of the block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockLayout {
+    /// Size in bytes of a whole codeword.
+    pub word_size: usize,
+    /// Index of the first payload byte in the codeword.
+    pub payload_offset: usize,
+    /// Number of payload bytes in each codeword.
+    pub payload_size: usize,
+}
+
+impl BlockLayout {
+    pub fn of<C: SecDedCodec + ?Sized>(codec: &C) -> Self {
+        let (encodable_size, code_size) = (codec.encodable_size(), codec.code_size());
+        let word_size = codec
+            .expected_slice_size()
+            .unwrap_or((encodable_size + code_size + 7) / 8);
+        let code_bytes = (code_size + 7) / 8;
+        let payload_size = (encodable_size + code_size).saturating_sub(code_bytes * 8) / 8;
+        BlockLayout {
+            word_size,
+            payload_offset: word_size - code_bytes - payload_size,
+            payload_size,
+        }
+    }
+
+    /// Returns the number of blocks required to store `length` bytes.
+    pub fn block_count(&self, length: usize) -> usize {
+        length / self.payload_size + usize::from(length % self.payload_size != 0)
+    }
+}
+
+/// The backend described by a frame's header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodecKind {
+    U64 = 0,
+    U128 = 1,
+    Dynamic = 2,
+}
+
+impl CodecKind {
+    pub fn of(codec: &SECDED) -> Self {
+        match codec {
+            SECDED::U64(_) => CodecKind::U64,
+            SECDED::U128(_) => CodecKind::U128,
+            #[cfg(feature = "dyn")]
+            SECDED::DYNAMIC(_) => CodecKind::Dynamic,
+        }
+    }
+
+    fn from_u8(kind: u8) -> Option<Self> {
+        match kind {
+            0 => Some(CodecKind::U64),
+            1 => Some(CodecKind::U128),
+            2 => Some(CodecKind::Dynamic),
+            _ => None,
+        }
+    }
+
+    /// Builds the codec of this kind, returning `None` if `encodable_size` is out of its range, or if the
+    /// `dyn` feature is required but disabled.
+    pub fn codec(self, encodable_size: usize) -> Option<SECDED> {
+        match self {
+            CodecKind::U64 if encodable_size <= 57 => {
+                Some(SECDED::U64(SecDed64::new(encodable_size)))
+            }
+            CodecKind::U128 if encodable_size <= 120 => {
+                Some(SECDED::U128(SecDed128::new(encodable_size)))
+            }
+            #[cfg(feature = "dyn")]
+            CodecKind::Dynamic if encodable_size <= 0xffff => {
+                Some(SECDED::DYNAMIC(SecDedDynamic::new(encodable_size)))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameError {
+    /// The frame is shorter than its header claims.
+    Truncated,
+    /// The header contained at least 2 errors in one of its words.
+    CorruptedHeader,
+    /// The header doesn't start with the expected magic bytes.
+    BadMagic,
+    UnsupportedVersion(u8),
+    /// The described codec can't be built by this version of the crate (or with these features).
+    UnsupportedCodec {
+        kind: u8,
+        encodable_size: u32,
+    },
+    /// The codec built from the header doesn't match the fingerprint recorded by the encoder.
+    FingerprintMismatch {
+        expected: u64,
+        found: u64,
+    },
+    /// The indices of the blocks that contained at least 2 errors.
+    Uncorrectable(Vec<usize>),
+}
+
+/// The decoded header of a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameHeader {
+    pub version: u8,
+    pub codec_kind: CodecKind,
+    pub encodable_size: u32,
+    pub fingerprint: u64,
+    /// Length of the original data, in bytes.
+    pub length: u64,
+    pub block_count: u64,
+}
+
+impl FrameHeader {
+    /// Size of the header once encoded, in bytes.
+    pub const ENCODED_SIZE: usize = HEADER_WORDS * 8;
+
+    fn header_codec() -> SecDed64 {
+        SecDed64::new(57)
+    }
+
+    pub fn encode(&self) -> [u8; FrameHeader::ENCODED_SIZE] {
+        let mut payload = [0u8; HEADER_WORDS * HEADER_WORD_PAYLOAD];
+        payload[..4].copy_from_slice(&MAGIC);
+        payload[4] = self.version;
+        payload[5] = self.codec_kind as u8;
+        byteorder::BigEndian::write_u32(&mut payload[6..10], self.encodable_size);
+        byteorder::BigEndian::write_u64(&mut payload[10..18], self.fingerprint);
+        byteorder::BigEndian::write_u64(&mut payload[18..26], self.length);
+        byteorder::BigEndian::write_u64(&mut payload[26..34], self.block_count);
+        let codec = Self::header_codec();
+        let mut encoded = [0u8; FrameHeader::ENCODED_SIZE];
+        for (word, chunk) in encoded
+            .chunks_mut(8)
+            .zip(payload.chunks(HEADER_WORD_PAYLOAD))
+        {
+            word[..HEADER_WORD_PAYLOAD].copy_from_slice(chunk);
+            codec.encode(word);
+        }
+        encoded
+    }
+
+    /// Decodes a header from the start of `frame`, correcting up to one error per header word.
+    pub fn decode(frame: &[u8]) -> Result<Self, FrameError> {
+        if frame.len() < FrameHeader::ENCODED_SIZE {
+            return Err(FrameError::Truncated);
+        }
+        let codec = Self::header_codec();
+        let mut payload = [0u8; HEADER_WORDS * HEADER_WORD_PAYLOAD];
+        for (word, chunk) in frame[..FrameHeader::ENCODED_SIZE]
+            .chunks(8)
+            .zip(payload.chunks_mut(HEADER_WORD_PAYLOAD))
+        {
+            let mut buffer = [0u8; 8];
+            buffer.copy_from_slice(word);
+            codec
+                .decode(&mut buffer)
+                .map_err(|()| FrameError::CorruptedHeader)?;
+            chunk.copy_from_slice(&buffer[..HEADER_WORD_PAYLOAD]);
+        }
+        if payload[..4] != MAGIC {
+            return Err(FrameError::BadMagic);
+        }
+        if payload[4] != FRAME_VERSION {
+            return Err(FrameError::UnsupportedVersion(payload[4]));
+        }
+        let encodable_size = byteorder::BigEndian::read_u32(&payload[6..10]);
+        let codec_kind = CodecKind::from_u8(payload[5]).ok_or(FrameError::UnsupportedCodec {
+            kind: payload[5],
+            encodable_size,
+        })?;
+        Ok(FrameHeader {
+            version: payload[4],
+            codec_kind,
+            encodable_size,
+            fingerprint: byteorder::BigEndian::read_u64(&payload[10..18]),
+            length: byteorder::BigEndian::read_u64(&payload[18..26]),
+            block_count: byteorder::BigEndian::read_u64(&payload[26..34]),
+        })
+    }
+}
+
+/// Splits data into blocks protected by its codec, and prepends them with a `FrameHeader`.
+pub struct FrameEncoder {
+    codec: SECDED,
+    layout: BlockLayout,
+}
+
+impl FrameEncoder {
+    /// # Panics:
+    /// Panics if `codec` can't carry at least one byte of payload per block.
+    pub fn new(codec: SECDED) -> Self {
+        let layout = BlockLayout::of(&codec);
+        if layout.payload_size == 0 {
+            panic!(
+                "A codec with an encodable size of {} bits can't carry a byte of payload per block",
+                codec.encodable_size()
+            );
+        }
+        FrameEncoder { codec, layout }
+    }
+
+    pub fn codec(&self) -> &SECDED {
+        &self.codec
+    }
+
+    pub fn layout(&self) -> BlockLayout {
+        self.layout
+    }
+
+    pub fn header(&self, length: usize) -> FrameHeader {
+        FrameHeader {
+            version: FRAME_VERSION,
+            codec_kind: CodecKind::of(&self.codec),
+            encodable_size: self.codec.encodable_size() as u32,
+            fingerprint: self.codec.fingerprint(),
+            length: length as u64,
+            block_count: self.layout.block_count(length) as u64,
+        }
+    }
+
+    /// Returns the size of the frame that encoding `length` bytes would produce.
+    pub fn encoded_size(&self, length: usize) -> usize {
+        FrameHeader::ENCODED_SIZE + self.layout.block_count(length) * self.layout.word_size
+    }
+
+    pub fn encode(&self, data: &[u8]) -> Vec<u8> {
+        let BlockLayout {
+            word_size,
+            payload_offset,
+            payload_size,
+        } = self.layout;
+        let mut frame = Vec::with_capacity(self.encoded_size(data.len()));
+        frame.extend_from_slice(&self.header(data.len()).encode());
+        for chunk in data.chunks(payload_size) {
+            let start = frame.len();
+            frame.resize(start + word_size, 0);
+            let block = &mut frame[start..];
+            block[payload_offset..payload_offset + chunk.len()].copy_from_slice(chunk);
+            self.codec.encode(block);
+        }
+        frame
+    }
+}
+
+/// Reads a frame's header, builds the codec it describes, and decodes its body.
+pub struct FrameDecoder<'a> {
+    header: FrameHeader,
+    codec: SECDED,
+    layout: BlockLayout,
+    body: &'a [u8],
+}
+
+impl<'a> FrameDecoder<'a> {
+    pub fn new(frame: &'a [u8]) -> Result<Self, FrameError> {
+        let header = FrameHeader::decode(frame)?;
+        let unsupported = FrameError::UnsupportedCodec {
+            kind: header.codec_kind as u8,
+            encodable_size: header.encodable_size,
+        };
+        let codec = header
+            .codec_kind
+            .codec(header.encodable_size as usize)
+            .ok_or_else(|| unsupported.clone())?;
+        let layout = BlockLayout::of(&codec);
+        if layout.payload_size == 0 {
+            return Err(unsupported);
+        }
+        let found = codec.fingerprint();
+        if found != header.fingerprint {
+            return Err(FrameError::FingerprintMismatch {
+                expected: header.fingerprint,
+                found,
+            });
+        }
+        // The sizes come from the frame, so they are only trusted once checked against the body: as
+        // `block_count` covers `length` in blocks of `payload_size` bytes, `length` is bounded by the body.
+        let body = &frame[FrameHeader::ENCODED_SIZE..];
+        let length = usize::try_from(header.length).map_err(|_| FrameError::Truncated)?;
+        let block_count = usize::try_from(header.block_count).map_err(|_| FrameError::Truncated)?;
+        let body_size = block_count
+            .checked_mul(layout.word_size)
+            .ok_or(FrameError::Truncated)?;
+        if block_count != layout.block_count(length) || body.len() < body_size {
+            return Err(FrameError::Truncated);
+        }
+        Ok(FrameDecoder {
+            body: &body[..body_size],
+            header,
+            codec,
+            layout,
+        })
+    }
+
+    pub fn header(&self) -> &FrameHeader {
+        &self.header
+    }
+
+    pub fn codec(&self) -> &SECDED {
+        &self.codec
+    }
+
+    pub fn layout(&self) -> BlockLayout {
+        self.layout
+    }
+
+    /// Decodes each block of the body in turn, correcting up to one error per block.
+    pub fn blocks(&self) -> impl Iterator<Item = FrameBlock> + '_ {
+        let BlockLayout {
+            word_size,
+            payload_offset,
+            payload_size,
+        } = self.layout;
+        let length = self.header.length as usize;
+        self.body
+            .chunks(word_size)
+            .enumerate()
+            .map(move |(index, received)| {
+                let payload = payload_offset..payload_offset + payload_size;
+                let mut word = received.to_vec();
+                let correction = self.codec.decode_with_correction(&mut word);
+                let repaired = match correction {
+                    Ok(Correction::None) => received.to_vec(),
+                    Ok(_) => {
+                        let mut repaired = vec![0u8; word_size];
+                        repaired[payload.clone()].copy_from_slice(&word[payload.clone()]);
+                        self.codec.encode(&mut repaired);
+                        repaired
+                    }
+                    Err(_) => {
+                        word.copy_from_slice(received);
+                        received.to_vec()
+                    }
+                };
+                let data_size = payload_size.min(length - index * payload_size);
+                FrameBlock {
+                    index,
+                    offset: FrameHeader::ENCODED_SIZE + index * word_size,
+                    correction,
+                    data: word[payload][..data_size].to_vec(),
+                    repaired,
+                }
+            })
+    }
+
+    /// Decodes the body, correcting up to one error per block.
+    /// # Returns:
+    /// The original data, or `FrameError::Uncorrectable` listing the blocks in which 2 errors were detected.
+    pub fn decode(&self) -> Result<Vec<u8>, FrameError> {
+        let mut data = Vec::with_capacity(self.header.length as usize);
+        let mut uncorrectable = Vec::new();
+        for block in self.blocks() {
+            if block.correction.is_err() {
+                uncorrectable.push(block.index);
+            }
+            data.extend_from_slice(&block.data);
+        }
+        if uncorrectable.is_empty() {
+            Ok(data)
+        } else {
+            Err(FrameError::Uncorrectable(uncorrectable))
+        }
+    }
+}
+
+/// A block of a frame's body, as decoded by `FrameDecoder::blocks`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameBlock {
+    pub index: usize,
+    /// Offset of the block in the frame, in bytes.
+    pub offset: usize,
+    pub correction: Result<Correction, Uncorrectable>,
+    /// The block's share of the original data, left as received if the block is uncorrectable.
+    pub data: Vec<u8>,
+    /// The block re-encoded from its corrected payload, or as received if it was clean or uncorrectable.
+    pub repaired: Vec<u8>,
+}
+
+#[test]
+fn layout() {
+    let layout = BlockLayout::of(&SecDed64::new(57));
+    assert_eq!((layout.word_size, layout.payload_offset), (8, 0));
+    assert_eq!(layout.payload_size, 7);
+    let layout = BlockLayout::of(&SecDed64::new(26));
+    assert_eq!((layout.payload_offset, layout.payload_size), (4, 3));
+    let layout = BlockLayout::of(&SecDed128::new(120));
+    assert_eq!((layout.payload_offset, layout.payload_size), (0, 15));
+}
+
+#[test]
+fn frame_roundtrip() {
+    let data: Vec<u8> = (0..200u32).map(|x| (x * 7) as u8).collect();
+    for &size in &[26, 57, 120] {
+        let encoder = FrameEncoder::new(SECDED::new(size));
+        let mut frame = encoder.encode(&data);
+        assert_eq!(frame.len(), encoder.encoded_size(data.len()));
+        for i in 0..FrameHeader::ENCODED_SIZE / 8 {
+            frame[i * 8 + i % 8] ^= 1 << (i % 7);
+        }
+        let word_size = encoder.layout().word_size;
+        for (i, block) in frame[FrameHeader::ENCODED_SIZE..]
+            .chunks_mut(word_size)
+            .enumerate()
+        {
+            block[i % word_size] ^= 1 << (i % 8);
+        }
+        let decoder = FrameDecoder::new(&frame).unwrap();
+        assert_eq!(decoder.header().encodable_size as usize, size);
+        assert_eq!(decoder.decode().unwrap(), data);
+    }
+}
+
+#[test]
+fn frame_errors() {
+    let encoder = FrameEncoder::new(SECDED::new(57));
+    let mut frame = encoder.encode(b"Hello, World!");
+    assert_eq!(
+        FrameDecoder::new(&frame[..45]).err(),
+        Some(FrameError::Truncated)
+    );
+    frame[FrameHeader::ENCODED_SIZE] ^= 0b11;
+    assert_eq!(
+        FrameDecoder::new(&frame).unwrap().decode(),
+        Err(FrameError::Uncorrectable(vec![0]))
+    );
+    frame[FrameHeader::ENCODED_SIZE + 9] ^= 0b10;
+    let decoder = FrameDecoder::new(&frame).unwrap();
+    let blocks: Vec<FrameBlock> = decoder.blocks().collect();
+    assert_eq!(blocks.len(), 2);
+    assert!(blocks[0].correction.is_err());
+    assert_eq!(blocks[0].repaired, frame[40..48]);
+    assert_eq!(blocks[1].offset, 48);
+    assert_eq!(blocks[1].correction.unwrap().bits(), vec![49]);
+    assert_eq!(blocks[1].data, b"World!");
+    assert_eq!(blocks[1].repaired, encoder.encode(b"Hello, World!")[48..]);
+    frame[FrameHeader::ENCODED_SIZE + 9] ^= 0b10;
+    let old = FrameHeader {
+        version: 1,
+        ..encoder.header(0)
+    };
+    assert_eq!(
+        FrameDecoder::new(&old.encode()).err(),
+        Some(FrameError::UnsupportedVersion(1))
+    );
+    frame[0] ^= 0b11;
+    assert_eq!(
+        FrameDecoder::new(&frame).err(),
+        Some(FrameError::CorruptedHeader)
+    );
+    let header = encoder.header(0);
+    for &(length, block_count) in &[
+        (u64::MAX, u64::MAX / 7 + 1),
+        (7 << 61, 1 << 61),
+        (1 << 20, 149797),
+    ] {
+        let forged = FrameHeader {
+            length,
+            block_count,
+            ..header
+        };
+        assert_eq!(
+            FrameDecoder::new(&forged.encode()).err(),
+            Some(FrameError::Truncated)
+        );
+    }
+}
@@ -0,0 +1,83 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+/// Primitive polynomials of `GF(2^m)`, indexed by `m`.
+pub const PRIMITIVE_POLYNOMIALS: [u32; 10] = [0, 0, 0x7, 0xb, 0x13, 0x25, 0x43, 0x89, 0x11d, 0x211];
+
+/// `GF(2^m)`, as exponential and logarithm tables.
+pub struct GaloisField {
+    pub size: usize,
+    pub exp: Vec<u16>,
+    pub log: Vec<u16>,
+}
+
+impl GaloisField {
+    pub fn new(m: usize) -> Self {
+        let (size, polynomial) = ((1 << m) - 1, PRIMITIVE_POLYNOMIALS[m]);
+        let (mut exp, mut log) = (vec![0u16; 2 * size], vec![0u16; size + 1]);
+        let mut x = 1u32;
+        for i in 0..size {
+            exp[i] = x as u16;
+            exp[i + size] = x as u16;
+            log[x as usize] = i as u16;
+            x <<= 1;
+            if x > size as u32 {
+                x ^= polynomial;
+            }
+        }
+        GaloisField { size, exp, log }
+    }
+
+    pub fn alpha(&self, power: usize) -> u16 {
+        self.exp[power % self.size]
+    }
+
+    pub fn mul(&self, a: u16, b: u16) -> u16 {
+        if a == 0 || b == 0 {
+            0
+        } else {
+            self.exp[self.log[a as usize] as usize + self.log[b as usize] as usize]
+        }
+    }
+
+    /// # Panics:
+    /// Panics if `b == 0`
+    pub fn div(&self, a: u16, b: u16) -> u16 {
+        assert!(b != 0, "Division by 0 in a Galois field");
+        if a == 0 {
+            0
+        } else {
+            self.exp[self.log[a as usize] as usize + self.size - self.log[b as usize] as usize]
+        }
+    }
+
+    /// Returns the minimal polynomial of `alpha^power` over `GF(2)`, bit `i` being the coefficient of `x^i`.
+    pub fn minimal_polynomial(&self, power: usize) -> u32 {
+        let mut conjugates = vec![power % self.size];
+        loop {
+            let next = conjugates[conjugates.len() - 1] * 2 % self.size;
+            if next == conjugates[0] {
+                break;
+            }
+            conjugates.push(next);
+        }
+        // The product of `(x + alpha^c)` for each conjugate `c`, with coefficients in `GF(2^m)`.
+        let mut product = vec![1u16];
+        for &c in conjugates.iter() {
+            let root = self.alpha(c);
+            let mut next = vec![0u16; product.len() + 1];
+            for (i, &coefficient) in product.iter().enumerate() {
+                next[i + 1] ^= coefficient;
+                next[i] ^= self.mul(coefficient, root);
+            }
+            product = next;
+        }
+        product
+            .iter()
+            .enumerate()
+            .fold(0, |polynomial, (i, &coefficient)| {
+                polynomial | u32::from(coefficient) << i
+            })
+    }
+}
@@ -0,0 +1,235 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::*;
+
+/// A plain Hamming code, without the overall parity bit of `SecDed64` and `SecDed128`: it uses the same
+/// `H` matrix, and thus one bit of code less.
+///
+/// It corrects any single error, but double errors are either miscorrected or, when their syndrome doesn't
+/// designate a bit of the shortened code, reported as uncorrectable: it only detects errors it can't correct
+/// by chance.
+///
+/// With `decode_with_erasures`, its minimum distance of 3 only guarantees that up to 2 erasures are
+/// recovered when no other error occurred, and that 1 erasure is recovered while 1 more error is detected.
+/// Beyond that, erasures may be filled wrong without any error reported.
+pub struct Hamming {
+    encodable_size: usize,
+    m: usize,
+    word_size: usize,
+    encode_matrix: [u128; 12],
+    syndromes: [u16; 128],
+}
+
+impl Hamming {
+    /// Builds a codec working on 8 bytes slices if `encodable_size + code_size() <= 64`, and 16 bytes slices
+    /// otherwise.
+    /// # Panics:
+    /// Panics if `encodable_size > 120`
+    pub fn new(encodable_size: usize) -> Self {
+        if encodable_size > 120 {
+            panic!("Hamming can only encode payloads of at most 120 bits");
+        }
+        let mut encode_matrix = SecDed128::new(encodable_size).encode_matrix;
+        // Dropping the overall parity bit moves every other bit one step to the right.
+        for row in encode_matrix.iter_mut() {
+            *row >>= 1;
+        }
+        let m = hamming_size(encodable_size);
+        let mut hamming = Hamming {
+            encodable_size,
+            m,
+            word_size: if encodable_size + m <= 64 { 8 } else { 16 },
+            encode_matrix,
+            syndromes: [0; 128],
+        };
+        for bit in 0..(encodable_size + m) {
+            hamming.syndromes[bit] = hamming.syndrome(1 << bit);
+        }
+        for (i, x) in hamming.syndromes[..(encodable_size + m)].iter().enumerate() {
+            assert_ne!(*x, 0);
+            for y in hamming.syndromes[i + 1..(encodable_size + m)].iter() {
+                assert_ne!(x, y);
+            }
+        }
+        hamming
+    }
+
+    fn syndrome(&self, value: u128) -> u16 {
+        self.encode_matrix[..self.m]
+            .iter()
+            .fold(0, |syndrome, row| {
+                syndrome << 1 | ((row & value).count_ones() & 1) as u16
+            })
+    }
+
+    fn read(&self, buffer: &[u8]) -> u128 {
+        if buffer.len() != self.word_size {
+            panic!(
+                "This Hamming codec works on {} bytes slices, not {}",
+                self.word_size,
+                buffer.len()
+            );
+        }
+        match self.word_size {
+            8 => u128::from(byteorder::BigEndian::read_u64(buffer)),
+            _ => byteorder::BigEndian::read_u128(buffer),
+        }
+    }
+
+    fn write(&self, buffer: &mut [u8], value: u128) {
+        match self.word_size {
+            8 => byteorder::BigEndian::write_u64(buffer, value as u64),
+            _ => byteorder::BigEndian::write_u128(buffer, value),
+        }
+    }
+}
+
+impl SecDedCodec for Hamming {
+    fn encodable_size(&self) -> usize {
+        self.encodable_size
+    }
+    fn code_size(&self) -> usize {
+        self.m
+    }
+    fn expected_slice_size(&self) -> Option<usize> {
+        Some(self.word_size)
+    }
+
+    /// Encodes the data IN-PLACE
+    /// # Arguments:
+    /// * `data`: The slice of data to encode. The last `code_size()` bits MUST be set to 0.
+    /// # Panics:
+    /// Panics if `data.len() != self.expected_slice_size().unwrap()`
+    fn encode(&self, data: &mut [u8]) {
+        let value = self.read(data);
+        self.write(data, value | u128::from(self.syndrome(value)));
+    }
+
+    /// Decodes the data IN-PLACE
+    /// # Returns:
+    /// `Ok(())` if no error was found, or if one bit was corrected, which may be a miscorrection if more
+    /// than one error occurred.
+    /// `Err(())` if the syndrome designates no bit of the codeword.
+    /// # Panics:
+    /// Panics if `data.len() != self.expected_slice_size().unwrap()`
+    fn decode(&self, data: &mut [u8]) -> Result<(), ()> {
+        match self.decode_with_correction(data) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(()),
+        }
+    }
+
+    fn decode_with_correction(&self, data: &mut [u8]) -> Result<Correction, Uncorrectable> {
+        let mut value = self.read(data);
+        let syndrome = self.syndrome(value);
+        let mask = (1u128 << self.m) - 1;
+        if syndrome == 0 {
+            self.write(data, value & !mask);
+            return Ok(Correction::None);
+        }
+        match self.syndromes[..(self.encodable_size + self.m)]
+            .iter()
+            .position(|s| *s == syndrome)
+        {
+            Some(bit) => {
+                value ^= 1 << bit;
+                self.write(data, value & !mask);
+                Ok(Correction::Bit {
+                    bit,
+                    syndrome: syndrome.into(),
+                })
+            }
+            None => Err(Uncorrectable {
+                syndrome: syndrome.into(),
+            }),
+        }
+    }
+}
+
+#[test]
+fn hamming() {
+    let hamming = Hamming::new(57);
+    assert_eq!(
+        (hamming.code_size(), hamming.expected_slice_size()),
+        (6, Some(8))
+    );
+    assert_eq!(Hamming::new(58).expected_slice_size(), Some(16));
+    let expected = [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xc0];
+    let mut encoded = expected;
+    hamming.encode(&mut encoded);
+    let mut buffer = encoded;
+    assert_eq!(hamming.decode(&mut buffer), Ok(()));
+    assert_eq!(buffer, expected);
+    let mut miscorrected = 0;
+    for i in 0..63 {
+        let mut buffer = encoded;
+        buffer[7 - i / 8] ^= 1 << (i % 8);
+        assert_eq!(hamming.decode(&mut buffer), Ok(()));
+        assert_eq!(buffer, expected);
+        for j in (i + 1)..63 {
+            let mut buffer = encoded;
+            buffer[7 - i / 8] ^= 1 << (i % 8);
+            buffer[7 - j / 8] ^= 1 << (j % 8);
+            if hamming.decode(&mut buffer).is_ok() {
+                assert_ne!(buffer, expected);
+                miscorrected += 1;
+            }
+        }
+    }
+    assert!(miscorrected > 0);
+}
+
+#[test]
+fn erasures() {
+    use crate::erasure::ErasureError;
+    use crate::fault::flip_bit;
+    let hamming = Hamming::new(26);
+    let expected = [0, 0, 0, 0, 0x12, 0x34, 0x56, 0x80];
+    let mut encoded = expected;
+    hamming.encode(&mut encoded);
+    for i in 0..31 {
+        for j in (i + 1)..31 {
+            for &flips in [&[][..], &[i], &[j], &[i, j]].iter() {
+                let mut buffer = encoded;
+                for &bit in flips {
+                    flip_bit(&mut buffer, bit);
+                }
+                assert_eq!(
+                    hamming.decode_with_erasures(&mut buffer, &[i, j]),
+                    Ok(flips.to_vec())
+                );
+                assert_eq!(buffer, expected);
+            }
+            for &(erased, flips) in [(i, &[j][..]), (i, &[i, j]), (j, &[i]), (j, &[i, j])].iter() {
+                let mut buffer = encoded;
+                for &bit in flips {
+                    flip_bit(&mut buffer, bit);
+                }
+                assert_eq!(
+                    hamming.decode_with_erasures(&mut buffer, &[erased]),
+                    Err(ErasureError::Uncorrectable)
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn configurable() {
+    use crate::parity::Parity;
+    // The scheme can be picked at runtime, without changing how the codec is used.
+    let schemes: Vec<Box<dyn SecDedCodec>> = vec![
+        Box::new(Parity::new(57)),
+        Box::new(Hamming::new(57)),
+        Box::new(SecDed64::new(57)),
+    ];
+    for codec in schemes.iter() {
+        let expected = [0, 0, 0, 0, 5, 0, 0, 0];
+        let mut buffer = expected;
+        codec.encode(&mut buffer);
+        assert_eq!(codec.decode(&mut buffer), Ok(()));
+        assert_eq!(buffer, expected);
+    }
+}
@@ -0,0 +1,480 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Synthesizable Verilog and VHDL for a codec's encoder, syndrome computer and corrector.
+//!
+//! The RTL is derived from the codec itself, by encoding each unit payload like `ReliabilityModel` does,
+//! so it produces and accepts exactly the codewords of the codec it was generated from. Bit `i` of an RTL
+//! codeword is bit `i` of the slice, counted from its right-most bit: the code takes the `code_size()`
+//! lowest bits, and the payload the `encodable_size()` bits above them.
+//!
+//! The syndrome is the code recomputed from the received payload, XORed with the received code: it is null
+//! for any codeword, and single errors are told apart by their syndromes exactly like the codec's `decode`
+//! does. Only single corrections are generated, so the RTL of codecs that correct more, such as `DecTed`
+//! or `SscDsd`, reports those errors as uncorrectable.
+
+use crate::*;
+use std::fmt::Write;
+
+/// Generates the RTL and test vectors of a codec.
+pub struct HdlGenerator<'a, C: ?Sized> {
+    codec: &'a C,
+    name: String,
+    encodable_size: usize,
+    code_size: usize,
+    /// The syndrome of a single error on each bit of the codeword.
+    columns: Vec<u64>,
+}
+
+impl<'a, C: SecDedCodec + ?Sized> HdlGenerator<'a, C> {
+    /// Builds a generator whose modules are prefixed with `name`.
+    /// # Panics:
+    /// Panics if `name` isn't a valid Verilog and VHDL identifier, or if `codec.code_size() > 64`.
+    pub fn new(codec: &'a C, name: &str) -> Self {
+        let valid = name.starts_with(|c: char| c.is_ascii_alphabetic())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && !name.ends_with('_')
+            && !name.contains("__");
+        if !valid {
+            panic!("{:?} isn't a valid Verilog and VHDL identifier", name);
+        }
+        let code_size = codec.code_size();
+        if code_size > 64 {
+            panic!("Can't generate RTL for a code of {} bits", code_size);
+        }
+        let columns = syndrome_columns(codec);
+        HdlGenerator {
+            codec,
+            name: name.into(),
+            encodable_size: codec.encodable_size(),
+            code_size,
+            columns,
+        }
+    }
+
+    fn word_size(&self) -> usize {
+        self.encodable_size + self.code_size
+    }
+
+    /// Returns the payload bits XORed into each code bit.
+    fn encoder_taps(&self) -> Vec<Vec<usize>> {
+        (0..self.code_size)
+            .map(|code_bit| {
+                (0..self.encodable_size)
+                    .filter(|&i| (self.columns[self.code_size + i] >> code_bit) & 1 == 1)
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the codeword bits XORed into each syndrome bit.
+    fn syndrome_taps(&self) -> Vec<Vec<usize>> {
+        (0..self.code_size)
+            .map(|code_bit| {
+                (0..self.word_size())
+                    .filter(|&i| (self.columns[i] >> code_bit) & 1 == 1)
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the syndrome that flips each bit of the codeword, `None` for the bits whose syndrome is
+    /// shared with another bit, which thus can't be corrected.
+    fn corrections(&self) -> Vec<Option<u64>> {
+        self.columns
+            .iter()
+            .map(|column| {
+                let shared = self.columns.iter().filter(|&x| x == column).count() > 1;
+                if shared {
+                    None
+                } else {
+                    Some(*column)
+                }
+            })
+            .collect()
+    }
+
+    fn binary(&self, syndrome: u64) -> String {
+        (0..self.code_size)
+            .rev()
+            .map(|bit| if (syndrome >> bit) & 1 == 1 { '1' } else { '0' })
+            .collect()
+    }
+
+    fn preamble(&self, comment: &str) -> String {
+        format!(
+            "{c} Generated by secded {} for a codec with {} bits of payload and {} bits of code.\n\
+             {c} Bit i of a codeword is bit i of the slice, counted from its right-most bit: the code takes\n\
+             {c} bits 0 to {}. Codewords must match fingerprint {:016x}.\n",
+            env!("CARGO_PKG_VERSION"),
+            self.encodable_size,
+            self.code_size,
+            self.code_size - 1,
+            self.codec.fingerprint(),
+            c = comment
+        )
+    }
+
+    /// Returns the Verilog modules `<name>_encoder`, `<name>_syndrome`, `<name>_corrector` and
+    /// `<name>_decoder`, the latter chaining the syndrome computer and the corrector.
+    pub fn verilog(&self) -> String {
+        let (name, k, m, n) = (
+            &self.name,
+            self.encodable_size,
+            self.code_size,
+            self.word_size(),
+        );
+        let xor = |signal: &str, taps: &[usize]| match taps.len() {
+            0 => "1'b0".to_string(),
+            _ => taps
+                .iter()
+                .map(|i| format!("{}[{}]", signal, i))
+                .collect::<Vec<_>>()
+                .join(" ^ "),
+        };
+        let mut rtl = self.preamble("//");
+        let _ = write!(
+            rtl,
+            "\nmodule {}_encoder (\n    input  wire [{}:0] data,\n    output wire [{}:0] codeword\n);\n    \
+             wire [{}:0] code;\n",
+            name,
+            k - 1,
+            n - 1,
+            m - 1
+        );
+        for (bit, taps) in self.encoder_taps().iter().enumerate() {
+            let _ = writeln!(rtl, "    assign code[{}] = {};", bit, xor("data", taps));
+        }
+        let _ = write!(
+            rtl,
+            "    assign codeword = {{data, code}};\nendmodule\n\n\
+             module {}_syndrome (\n    input  wire [{}:0] codeword,\n    output wire [{}:0] syndrome\n);\n",
+            name,
+            n - 1,
+            m - 1
+        );
+        for (bit, taps) in self.syndrome_taps().iter().enumerate() {
+            let _ = writeln!(
+                rtl,
+                "    assign syndrome[{}] = {};",
+                bit,
+                xor("codeword", taps)
+            );
+        }
+        let _ = write!(
+            rtl,
+            "endmodule\n\nmodule {}_corrector (\n    input  wire [{}:0] codeword,\n    input  wire [{}:0] syndrome,\n    \
+             output wire [{}:0] data,\n    output wire corrected,\n    output wire uncorrectable\n);\n    \
+             wire [{}:0] flip;\n",
+            name,
+            n - 1,
+            m - 1,
+            k - 1,
+            n - 1
+        );
+        for (bit, correction) in self.corrections().iter().enumerate() {
+            match correction {
+                Some(syndrome) => {
+                    let binary = self.binary(*syndrome);
+                    let _ = writeln!(
+                        rtl,
+                        "    assign flip[{}] = syndrome == {}'b{};",
+                        bit, m, binary
+                    );
+                }
+                None => {
+                    let _ = writeln!(rtl, "    assign flip[{}] = 1'b0;", bit);
+                }
+            }
+        }
+        let _ = write!(
+            rtl,
+            "    assign data = codeword[{n1}:{m}] ^ flip[{n1}:{m}];\n    assign corrected = |flip;\n    \
+             assign uncorrectable = (|syndrome) & ~corrected;\nendmodule\n\n\
+             module {name}_decoder (\n    input  wire [{n1}:0] codeword,\n    output wire [{k1}:0] data,\n    \
+             output wire corrected,\n    output wire uncorrectable\n);\n    wire [{m1}:0] syndrome;\n    \
+             {name}_syndrome syndrome_computer (.codeword(codeword), .syndrome(syndrome));\n    \
+             {name}_corrector corrector (\n        .codeword(codeword),\n        .syndrome(syndrome),\n        \
+             .data(data),\n        .corrected(corrected),\n        .uncorrectable(uncorrectable)\n    );\n\
+             endmodule\n",
+            name = name,
+            n1 = n - 1,
+            m = m,
+            m1 = m - 1,
+            k1 = k - 1
+        );
+        rtl
+    }
+
+    /// Returns the VHDL entities `<name>_encoder`, `<name>_syndrome`, `<name>_corrector` and
+    /// `<name>_decoder`, the latter chaining the syndrome computer and the corrector.
+    pub fn vhdl(&self) -> String {
+        let (name, k, m, n) = (
+            &self.name,
+            self.encodable_size,
+            self.code_size,
+            self.word_size(),
+        );
+        let xor = |signal: &str, taps: &[usize]| match taps.len() {
+            0 => "'0'".to_string(),
+            _ => taps
+                .iter()
+                .map(|i| format!("{}({})", signal, i))
+                .collect::<Vec<_>>()
+                .join(" xor "),
+        };
+        let libraries = "library ieee;\nuse ieee.std_logic_1164.all;\n";
+        let mut rtl = self.preamble("--");
+        let _ = write!(
+            rtl,
+            "\n{}\nentity {name}_encoder is\n    port (\n        data     : in  std_logic_vector({k1} downto 0);\n        \
+             codeword : out std_logic_vector({n1} downto 0)\n    );\nend entity;\n\n\
+             architecture rtl of {name}_encoder is\n    signal code : std_logic_vector({m1} downto 0);\nbegin\n",
+            libraries,
+            name = name,
+            k1 = k - 1,
+            n1 = n - 1,
+            m1 = m - 1
+        );
+        for (bit, taps) in self.encoder_taps().iter().enumerate() {
+            let _ = writeln!(rtl, "    code({}) <= {};", bit, xor("data", taps));
+        }
+        let _ = write!(
+            rtl,
+            "    codeword <= data & code;\nend architecture;\n\n{}\nentity {name}_syndrome is\n    port (\n        \
+             codeword : in  std_logic_vector({n1} downto 0);\n        syndrome : out std_logic_vector({m1} downto 0)\n    \
+             );\nend entity;\n\narchitecture rtl of {name}_syndrome is\nbegin\n",
+            libraries,
+            name = name,
+            n1 = n - 1,
+            m1 = m - 1
+        );
+        for (bit, taps) in self.syndrome_taps().iter().enumerate() {
+            let _ = writeln!(rtl, "    syndrome({}) <= {};", bit, xor("codeword", taps));
+        }
+        let _ = write!(
+            rtl,
+            "end architecture;\n\n{}\nentity {name}_corrector is\n    port (\n        \
+             codeword      : in  std_logic_vector({n1} downto 0);\n        \
+             syndrome      : in  std_logic_vector({m1} downto 0);\n        \
+             data          : out std_logic_vector({k1} downto 0);\n        corrected     : out std_logic;\n        \
+             uncorrectable : out std_logic\n    );\nend entity;\n\narchitecture rtl of {name}_corrector is\n    \
+             constant NO_FLIP : std_logic_vector({n1} downto 0) := (others => '0');\n    \
+             constant NO_SYNDROME : std_logic_vector({m1} downto 0) := (others => '0');\n    \
+             signal flip : std_logic_vector({n1} downto 0);\n    signal flipped : std_logic;\nbegin\n",
+            libraries,
+            name = name,
+            n1 = n - 1,
+            m1 = m - 1,
+            k1 = k - 1
+        );
+        for (bit, correction) in self.corrections().iter().enumerate() {
+            match correction {
+                Some(syndrome) => {
+                    let binary = self.binary(*syndrome);
+                    let _ = writeln!(
+                        rtl,
+                        "    flip({}) <= '1' when syndrome = \"{}\" else '0';",
+                        bit, binary
+                    );
+                }
+                None => {
+                    let _ = writeln!(rtl, "    flip({}) <= '0';", bit);
+                }
+            }
+        }
+        let _ = write!(
+            rtl,
+            "    data <= codeword({n1} downto {m}) xor flip({n1} downto {m});\n    \
+             flipped <= '0' when flip = NO_FLIP else '1';\n    corrected <= flipped;\n    \
+             uncorrectable <= '1' when syndrome /= NO_SYNDROME and flipped = '0' else '0';\n\
+             end architecture;\n\n{}\nentity {name}_decoder is\n    port (\n        \
+             codeword      : in  std_logic_vector({n1} downto 0);\n        \
+             data          : out std_logic_vector({k1} downto 0);\n        corrected     : out std_logic;\n        \
+             uncorrectable : out std_logic\n    );\nend entity;\n\narchitecture rtl of {name}_decoder is\n    \
+             signal syndrome : std_logic_vector({m1} downto 0);\nbegin\n    \
+             syndrome_computer : entity work.{name}_syndrome port map (codeword => codeword, syndrome => syndrome);\n    \
+             corrector : entity work.{name}_corrector port map (\n        codeword => codeword,\n        \
+             syndrome => syndrome,\n        data => data,\n        corrected => corrected,\n        \
+             uncorrectable => uncorrectable\n    );\nend architecture;\n",
+            libraries,
+            name = name,
+            n1 = n - 1,
+            m = m,
+            m1 = m - 1,
+            k1 = k - 1
+        );
+        rtl
+    }
+
+    /// Returns the Verilog module `<name>_testbench`, which runs `<name>_encoder` and `<name>_decoder` on
+    /// each line of the `test_vectors` stored at `vectors_path`, and reports the mismatches.
+    pub fn verilog_testbench(&self, vectors_path: &str) -> String {
+        let mut testbench = self.preamble("//");
+        let _ = write!(
+            testbench,
+            "\nmodule {name}_testbench;\n    reg [{k1}:0] payload, expected;\n    reg [{n1}:0] codeword, received;\n    \
+             integer status, file, vectors, failures;\n    wire [{n1}:0] encoded;\n    wire [{k1}:0] data;\n    \
+             wire corrected, uncorrectable;\n\n    \
+             {name}_encoder encoder (.data(payload), .codeword(encoded));\n    {name}_decoder decoder (\n        \
+             .codeword(received),\n        .data(data),\n        .corrected(corrected),\n        \
+             .uncorrectable(uncorrectable)\n    );\n\n    initial begin\n        vectors = 0;\n        failures = 0;\n        \
+             file = $fopen(\"{path}\", \"r\");\n        \
+             while ($fscanf(file, \"%h %h %h %h %d\\n\", payload, codeword, received, expected, status) == 5) begin\n            \
+             #1;\n            vectors = vectors + 1;\n            \
+             if (encoded !== codeword || data !== expected || corrected !== (status == 1)\n                    \
+             || uncorrectable !== (status == 2)) begin\n                failures = failures + 1;\n                \
+             $display(\"mismatch: %h %h %h %h %0d\", payload, codeword, received, expected, status);\n            \
+             end\n        end\n        $fclose(file);\n        $display(\"%0d vectors, %0d failures\", vectors, failures);\n        \
+             $finish;\n    end\nendmodule\n",
+            name = self.name,
+            k1 = self.encodable_size - 1,
+            n1 = self.word_size() - 1,
+            path = vectors_path
+        );
+        testbench
+    }
+
+    /// Returns `bits` bits of `buffer` from its `from`th right-most bit, in hexadecimal.
+    fn hex(buffer: &[u8], from: usize, bits: usize) -> String {
+        (0..(bits + 3) / 4)
+            .rev()
+            .map(|digit| {
+                let value = (0..4).filter(|i| digit * 4 + i < bits).fold(0, |value, i| {
+                    value | nth_bit_from_right(buffer, from + digit * 4 + i) << i
+                });
+                std::char::from_digit(u32::from(value), 16).unwrap()
+            })
+            .collect()
+    }
+
+    /// Returns `count` test vectors for each error weight from 0 to 2, computed by the codec from random
+    /// payloads drawn from `seed`.
+    ///
+    /// Each line holds, in hexadecimal and separated by spaces: the payload, its codeword, the received
+    /// codeword, the payload `<name>_decoder` must output; and then the expected status in decimal: 0 if
+    /// the codeword was clean, 1 if a bit was corrected, 2 if the errors are uncorrectable. Like the RTL,
+    /// the vectors report the errors that codecs such as `DecTed` correct on several bits as uncorrectable,
+    /// with the received payload as output.
+    pub fn test_vectors(&self, count: usize, seed: u64) -> String {
+        let (k, m, n) = (self.encodable_size, self.code_size, self.word_size());
+        let mut lines = String::new();
+        for vector in vectors::test_vectors(self.codec, count, seed) {
+            let _ = writeln!(
+                lines,
+                "{} {} {} {} {}",
+                Self::hex(&vector.payload, m, k),
+                Self::hex(&vector.codeword, 0, n),
+                Self::hex(&vector.received, 0, n),
+                Self::hex(&vector.decoded, m, k),
+                vector.status
+            );
+        }
+        lines
+    }
+}
+
+/// Evaluates the generated netlist on `codeword`, returning the output of `<name>_decoder`.
+#[cfg(test)]
+fn simulate<C: SecDedCodec + ?Sized>(
+    generator: &HdlGenerator<C>,
+    codeword: &[bool],
+) -> (Vec<bool>, u8) {
+    let syndrome =
+        generator
+            .syndrome_taps()
+            .iter()
+            .enumerate()
+            .fold(0u64, |syndrome, (bit, taps)| {
+                let value = taps.iter().fold(false, |value, &i| value ^ codeword[i]);
+                syndrome | u64::from(value) << bit
+            });
+    let flip: Vec<bool> = generator
+        .corrections()
+        .iter()
+        .map(|correction| *correction == Some(syndrome))
+        .collect();
+    let data = (generator.code_size..codeword.len())
+        .map(|i| codeword[i] ^ flip[i])
+        .collect();
+    let status = match (syndrome, flip.contains(&true)) {
+        (0, _) => 0,
+        (_, true) => 1,
+        _ => 2,
+    };
+    (data, status)
+}
+
+#[cfg(test)]
+fn bits(hex: &str, size: usize) -> Vec<bool> {
+    let value: Vec<u32> = hex.chars().rev().map(|c| c.to_digit(16).unwrap()).collect();
+    (0..size)
+        .map(|i| (value[i / 4] >> (i % 4)) & 1 == 1)
+        .collect()
+}
+
+#[test]
+fn netlist_matches_codecs() {
+    let codecs: Vec<Box<dyn SecDedCodec>> = vec![
+        Box::new(SecDed64::new(57)),
+        Box::new(SecDed128::new(100)),
+        Box::new(SecDed64::new_sbd(32)),
+        Box::new(Hamming::new(26)),
+        Box::new(Parity::new(15)),
+        Box::new(DecTed::new(26)),
+        Box::new(SscDsd::new(4, 32)),
+    ];
+    for codec in codecs.iter() {
+        let generator = HdlGenerator::new(codec.as_ref(), "secded");
+        let (k, n) = (codec.encodable_size(), generator.word_size());
+        let vectors = generator.test_vectors(200, 47);
+        for line in vectors.lines() {
+            let fields: Vec<&str> = line.split(' ').collect();
+            let payload = bits(fields[0], k);
+            let codeword = bits(fields[1], n);
+            // The encoder's equations are the syndrome's, applied to the payload alone.
+            let encoded: Vec<bool> = generator
+                .encoder_taps()
+                .iter()
+                .map(|taps| taps.iter().fold(false, |value, &i| value ^ payload[i]))
+                .chain(payload.iter().cloned())
+                .collect();
+            assert_eq!(encoded, codeword, "{}", line);
+            let (data, status) = simulate(&generator, &bits(fields[2], n));
+            assert_eq!(
+                (data, status),
+                (bits(fields[3], k), fields[4].parse().unwrap()),
+                "{}",
+                line
+            );
+        }
+    }
+}
+
+#[test]
+fn rtl() {
+    let codec = SecDed64::new(57);
+    let generator = HdlGenerator::new(&codec, "secded64");
+    let verilog = generator.verilog();
+    for module in ["encoder", "syndrome", "corrector", "decoder"].iter() {
+        assert!(verilog.contains(&format!("module secded64_{} (", module)));
+        assert!(generator
+            .vhdl()
+            .contains(&format!("entity secded64_{} is", module)));
+    }
+    let testbench = generator.verilog_testbench("secded64_vectors.txt");
+    assert!(testbench.contains("file = $fopen(\"secded64_vectors.txt\", \"r\");"));
+    assert!(testbench.contains("%h %h %h %h %d\\n"));
+    assert_eq!(
+        verilog.matches("module ").count(),
+        verilog.matches("endmodule").count()
+    );
+    assert!(verilog.contains("    input  wire [56:0] data,\n    output wire [63:0] codeword\n"));
+    // A code bit is only covered by its own syndrome bit, and the first data bit sits right above the code.
+    assert!(verilog.contains("    assign flip[2] = syndrome == 7'b0000100;\n"));
+    assert!(verilog.contains("    assign flip[7] = syndrome == 7'b1111111;\n"));
+    assert!(generator
+        .vhdl()
+        .contains("    flip(7) <= '1' when syndrome = \"1111111\" else '0';\n"));
+}
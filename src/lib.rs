@@ -21,14 +21,58 @@ pub use secded_128::SecDed128;
 #[cfg(feature = "dyn")]
 pub mod secded_dynamic;
 
+pub mod c_source;
+pub use c_source::CSourceGenerator;
+pub mod crc;
+pub use crc::{Crc, WithCrc};
+pub mod dec_ted;
+pub use dec_ted::DecTed;
+pub mod erasure;
+pub mod fault;
+pub mod fingerprint;
+pub use fingerprint::Fingerprinted;
+pub mod frame;
+pub use frame::{FrameDecoder, FrameEncoder};
+mod galois;
+pub mod hamming;
+pub use hamming::Hamming;
+pub mod hdl;
+pub use hdl::HdlGenerator;
+pub mod parity;
+pub use parity::Parity;
+pub mod product;
+pub use product::ProductCode;
+pub mod protected;
+pub use protected::Protected;
+pub mod reliability;
+mod sbd;
+pub mod scrub;
+pub use scrub::Scrubber;
+pub mod secded_vec;
+pub use secded_vec::SecDedVec;
+pub mod simulation;
+pub mod soft;
+pub use soft::SoftDecoder;
+pub mod ssc_dsd;
+pub use ssc_dsd::SscDsd;
+pub mod stats;
+pub use stats::Statistics;
+pub mod vectors;
+pub use vectors::GoldenVectors;
+
 fn hamming_size(encodable_size: usize) -> usize {
     let mut m = 1;
-    while (1 << m) - m - 1 < encodable_size as usize {
+    while (1 << m) - m - 1 < encodable_size {
         m += 1;
     }
     m
 }
 
+/// Returns the `n`th bit of `buffer`, counting from the right-most bit of the last byte.
+fn nth_bit_from_right(buffer: &[u8], n: usize) -> u8 {
+    (buffer[buffer.len() - 1 - n / 8] >> (n % 8)) & 1
+}
+
 /// Your main interaction point with this crate, it allows you to encode and decode your data slices.
 pub trait SecDedCodec {
     /// Returns the number of bits that this SecDedCodec can encode.
@@ -42,6 +86,14 @@ pub trait SecDedCodec {
         None
     }
 
+    /// Returns a stable hash of the codec's `H` matrix and bit layout.
+    ///
+    /// Two codecs with the same fingerprint produce and accept the same codewords, which makes it suitable
+    /// for exchange during a handshake: see `Fingerprinted::decode_from`.
+    fn fingerprint(&self) -> u64 {
+        fingerprint::fingerprint(self)
+    }
+
     /// Encodes the data IN-PLACE
     /// # Arguments:
     /// * `data`: The slice of data to encode. The last `secded.code_size()` bits MUST be set to 0.
@@ -58,7 +110,7 @@ pub trait SecDedCodec {
     /// Decodes the data IN-PLACE
     /// # Arguments:
     /// * `data`: The slice of data to decode.  
-    /// The last `secded.code_size()` bits will be reset to 0, a single error will be corrected implicitly.
+    ///   The last `secded.code_size()` bits will be reset to 0, a single error will be corrected implicitly.
     /// # Returns:
     /// `Ok(())` if the data slice's correctness has been checked: 0 error found or 1 found and corrected.
     /// `Err(())` if 2 errors were detected.
@@ -67,9 +119,176 @@ pub trait SecDedCodec {
     /// * SecDed64 panics if `data.len() != 8`
     /// * SecDed128 panics if `data.len() != 16`
     /// * You can use `secded.expected_slice_size()` to find out if a specific size is required for the slice.
+    #[allow(clippy::result_unit_err)]
     fn decode(&self, data: &mut [u8]) -> Result<(), ()>;
+
+    /// Decodes the data IN-PLACE, exactly like `decode`, but reports which bit was corrected.
+    /// # Returns:
+    /// `Ok(Correction::None)` if no error was found, `Ok(Correction::Bit { .. })` if one was found and corrected.
+    /// Double-error-correcting codecs return `Ok(Correction::Double { .. })` when 2 errors were corrected.
+    /// `Err(Uncorrectable { .. })` if 2 errors were detected.
+    ///
+    /// The default implementation finds the corrected bit by comparing the payload before and after `decode`,
+    /// and can't report syndromes nor corrections in the code bits: this crate's codecs override it.
+    fn decode_with_correction(&self, data: &mut [u8]) -> Result<Correction, Uncorrectable> {
+        let before = data.to_vec();
+        self.decode(data)
+            .map_err(|()| Uncorrectable { syndrome: 0 })?;
+        let total_size = self.encodable_size() + self.code_size();
+        Ok((self.code_size()..total_size)
+            .find(|&bit| nth_bit_from_right(&before, bit) != nth_bit_from_right(data, bit))
+            .map_or(Correction::None, |bit| Correction::Bit { bit, syndrome: 0 }))
+    }
+
+    /// Decodes the data IN-PLACE, knowing that the bits listed in `erasures` are unreliable.
+    /// # Arguments:
+    /// * `data`: The slice of data to decode.  
+    ///   The last `secded.code_size()` bits will be reset to 0 if decoding succeeds.
+    /// * `erasures`: The indices of the unreliable bits, counted from the right-most bit of the slice.
+    /// # Returns:
+    /// `Ok(bits)`, `bits` being the sorted indices of the bits that were flipped back.
+    /// Without erasures, this behaves like `decode_with_correction`. Otherwise, only the erased bits may
+    /// be corrected, and for codes of minimum distance 4 or more, such as those of `SecDed64`, `SecDed128`
+    /// and `SecDedDynamic`, any codeword with up to 3 erasures and at most `3 - erasures.len()` other errors
+    /// is either decoded correctly or rejected:
+    /// * up to 3 erasures are always recovered when no other error occurred,
+    /// * 2 erasures are recovered, and 1 more error is detected,
+    /// * 1 erasure is recovered, and up to 2 more errors are detected.
+    ///
+    /// Codecs with weaker codes, such as `Parity` and `Hamming`, document their own guarantees on their type.
+    /// `Err(ErasureError::Uncorrectable)` if no filling of the erasures is consistent with the other bits,
+    /// or if several are. `Err(ErasureError::TooManyErasures(n))` if more than `erasure::MAX_ERASURES`
+    /// distinct bits were given.
+    /// # Panics:
+    /// Panics if an erasure doesn't designate one of the `encodable_size() + code_size()` right-most bits,
+    /// and whenever `decode` would.
+    fn decode_with_erasures(
+        &self,
+        data: &mut [u8],
+        erasures: &[usize],
+    ) -> Result<Vec<usize>, erasure::ErasureError> {
+        erasure::decode_with_erasures(self, data, erasures)
+    }
+}
+
+impl<C: SecDedCodec + ?Sized> SecDedCodec for &C {
+    fn encodable_size(&self) -> usize {
+        (**self).encodable_size()
+    }
+    fn code_size(&self) -> usize {
+        (**self).code_size()
+    }
+    fn expected_slice_size(&self) -> Option<usize> {
+        (**self).expected_slice_size()
+    }
+    fn fingerprint(&self) -> u64 {
+        (**self).fingerprint()
+    }
+    fn encode(&self, data: &mut [u8]) {
+        (**self).encode(data)
+    }
+    fn decode(&self, data: &mut [u8]) -> Result<(), ()> {
+        (**self).decode(data)
+    }
+    fn decode_with_correction(&self, data: &mut [u8]) -> Result<Correction, Uncorrectable> {
+        (**self).decode_with_correction(data)
+    }
+    fn decode_with_erasures(
+        &self,
+        data: &mut [u8],
+        erasures: &[usize],
+    ) -> Result<Vec<usize>, erasure::ErasureError> {
+        (**self).decode_with_erasures(data, erasures)
+    }
+}
+
+/// Returns the code produced by encoding each unit payload, from the right-most data bit, split in 64 bits
+/// limbs from the right-most code bit: XORed together, they give the code of any payload.
+pub(crate) fn unit_codes<C: SecDedCodec + ?Sized>(codec: &C) -> Vec<Vec<u64>> {
+    let (encodable_size, code_size) = (codec.encodable_size(), codec.code_size());
+    let word_size = frame::BlockLayout::of(codec).word_size;
+    let mut buffer = vec![0u8; word_size];
+    (code_size..(code_size + encodable_size))
+        .map(|data_bit| {
+            for x in buffer.iter_mut() {
+                *x = 0;
+            }
+            buffer[word_size - 1 - data_bit / 8] = 1 << (data_bit % 8);
+            codec.encode(&mut buffer);
+            let mut code = vec![0u64; (code_size + 63) / 64];
+            for bit in 0..code_size {
+                code[bit / 64] |= u64::from(nth_bit_from_right(&buffer, bit)) << (bit % 64);
+            }
+            code
+        })
+        .collect()
+}
+
+/// Returns the code of each unit payload like `unit_codes`, for codecs whose `code_size()` is at most 64.
+pub(crate) fn data_columns<C: SecDedCodec + ?Sized>(codec: &C) -> Vec<u64> {
+    unit_codes(codec)
+        .into_iter()
+        .map(|code| code.first().copied().unwrap_or(0))
+        .collect()
 }
 
+/// Returns the syndrome of a single error on each bit of the codeword, the syndrome being the code of the
+/// received payload XORed with the received code: a code bit's is itself, and a data bit's its column.
+/// Only meaningful for codecs whose `code_size()` is at most 64.
+pub(crate) fn syndrome_columns<C: SecDedCodec + ?Sized>(codec: &C) -> Vec<u64> {
+    let mut columns: Vec<u64> = (0..codec.code_size()).map(|bit| 1 << bit).collect();
+    columns.extend(data_columns(codec));
+    columns
+}
+
+/// A successful decoding's report, see `SecDedCodec::decode_with_correction`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Correction {
+    /// The codeword was clean.
+    None,
+    /// A single error was corrected: `bit` counts from the right-most bit of the slice, so indices below
+    /// `code_size()` designate bits of the correction code.
+    Bit { bit: usize, syndrome: u64 },
+    /// Two errors were corrected, which only double-error-correcting codecs such as `DecTed` report.
+    /// `bits` are in increasing order.
+    Double { bits: [usize; 2], syndrome: u64 },
+    /// The errors confined to the `symbol`th symbol of `size` bits were corrected, which only symbol codecs
+    /// such as `SscDsd` report: bit `i` of `error` was flipped back at index `symbol * size + i`.
+    Symbol {
+        symbol: usize,
+        size: usize,
+        error: u8,
+        syndrome: u64,
+    },
+}
+
+impl Correction {
+    /// Returns the indices of the corrected bits, in increasing order.
+    pub fn bits(&self) -> Vec<usize> {
+        match *self {
+            Correction::None => Vec::new(),
+            Correction::Bit { bit, .. } => vec![bit],
+            Correction::Double { bits, .. } => bits.to_vec(),
+            Correction::Symbol {
+                symbol,
+                size,
+                error,
+                ..
+            } => (0..size)
+                .filter(|i| (error >> i) & 1 == 1)
+                .map(|i| symbol * size + i)
+                .collect(),
+        }
+    }
+}
+
+/// Returned by `SecDedCodec::decode_with_correction` when at least 2 errors were detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Uncorrectable {
+    pub syndrome: u64,
+}
+
+#[allow(clippy::large_enum_variant)]
 pub enum SECDED {
     U64(SecDed64),
     U128(SecDed128),
@@ -91,6 +310,57 @@ impl SECDED {
     }
 }
 
+impl SecDedCodec for SECDED {
+    fn encodable_size(&self) -> usize {
+        match self {
+            SECDED::U64(secded) => secded.encodable_size(),
+            SECDED::U128(secded) => secded.encodable_size(),
+            #[cfg(feature = "dyn")]
+            SECDED::DYNAMIC(secded) => secded.encodable_size(),
+        }
+    }
+    fn code_size(&self) -> usize {
+        match self {
+            SECDED::U64(secded) => secded.code_size(),
+            SECDED::U128(secded) => secded.code_size(),
+            #[cfg(feature = "dyn")]
+            SECDED::DYNAMIC(secded) => secded.code_size(),
+        }
+    }
+    fn expected_slice_size(&self) -> Option<usize> {
+        match self {
+            SECDED::U64(secded) => secded.expected_slice_size(),
+            SECDED::U128(secded) => secded.expected_slice_size(),
+            #[cfg(feature = "dyn")]
+            SECDED::DYNAMIC(secded) => secded.expected_slice_size(),
+        }
+    }
+    fn encode(&self, data: &mut [u8]) {
+        match self {
+            SECDED::U64(secded) => secded.encode(data),
+            SECDED::U128(secded) => secded.encode(data),
+            #[cfg(feature = "dyn")]
+            SECDED::DYNAMIC(secded) => secded.encode(data),
+        }
+    }
+    fn decode(&self, data: &mut [u8]) -> Result<(), ()> {
+        match self {
+            SECDED::U64(secded) => secded.decode(data),
+            SECDED::U128(secded) => secded.decode(data),
+            #[cfg(feature = "dyn")]
+            SECDED::DYNAMIC(secded) => secded.decode(data),
+        }
+    }
+    fn decode_with_correction(&self, data: &mut [u8]) -> Result<Correction, Uncorrectable> {
+        match self {
+            SECDED::U64(secded) => secded.decode_with_correction(data),
+            SECDED::U128(secded) => secded.decode_with_correction(data),
+            #[cfg(feature = "dyn")]
+            SECDED::DYNAMIC(secded) => secded.decode_with_correction(data),
+        }
+    }
+}
+
 #[cfg(feature = "ffi")]
-#[allow(non_snake_case)]
+#[allow(non_snake_case, non_camel_case_types)]
 mod ffi;
@@ -0,0 +1,390 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The `secded` command-line tool, which protects files with frames (see `secded::frame`).
+
+use secded::frame::{BlockLayout, CodecKind, FrameDecoder, FrameError, FrameHeader};
+use secded::{Correction, FrameEncoder};
+use std::io::{Read, Write};
+
+const USAGE: &str = "\
+Usage: secded <COMMAND> [OPTIONS] [INPUT]
+
+Commands:
+    encode    Protects INPUT, writing a frame
+    decode    Decodes a frame, writing the original data
+    verify    Checks a frame, writing nothing
+    repair    Corrects a frame, writing it back with every correctable block repaired
+
+INPUT defaults to stdin, and can also be `-`.
+
+Options:
+    -o, --output <FILE>       Writes to FILE instead of stdout
+    -b, --backend <BACKEND>   64, 128, dyn (requires the `dyn` feature) or auto [default: auto]
+    -s, --block-size <BITS>   Payload bits per block, at encoding [default: 57 for 64, 120 otherwise]
+    -q, --quiet               Doesn't report damaged blocks
+    -h, --help                Prints this message
+
+decode, verify and repair report each damaged block with its offset in the frame on stderr.
+Exit codes: 0 on success, 1 on invalid arguments, I/O errors or unreadable frames, 2 if a block is
+uncorrectable, in which case decode writes nothing.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Encode,
+    Decode,
+    Verify,
+    Repair,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Backend {
+    Auto,
+    Kind(CodecKind),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Options {
+    command: Command,
+    input: Option<String>,
+    output: Option<String>,
+    backend: Backend,
+    block_size: Option<usize>,
+    quiet: bool,
+}
+
+/// Parses the arguments, returning `None` if help was requested.
+fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Option<Options>, String> {
+    let mut args = args.into_iter();
+    let (mut command, mut input, mut output) = (None, None, None);
+    let (mut backend, mut block_size, mut quiet) = (None, None, false);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("`{}` expects a value", arg))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-q" | "--quiet" => quiet = true,
+            "-o" | "--output" => output = Some(value()?),
+            "-b" | "--backend" => {
+                backend = Some(match value()?.as_str() {
+                    "auto" => Backend::Auto,
+                    "64" => Backend::Kind(CodecKind::U64),
+                    "128" => Backend::Kind(CodecKind::U128),
+                    "dyn" => Backend::Kind(CodecKind::Dynamic),
+                    other => return Err(format!("unknown backend `{}`", other)),
+                })
+            }
+            "-s" | "--block-size" => {
+                let value = value()?;
+                block_size = match value.parse() {
+                    Ok(0) | Err(_) => return Err(format!("invalid block size `{}`", value)),
+                    Ok(size) => Some(size),
+                }
+            }
+            flag if flag.starts_with('-') && flag != "-" => {
+                return Err(format!("unknown option `{}`", flag))
+            }
+            _ if command.is_none() => {
+                command = Some(match arg.as_str() {
+                    "encode" => Command::Encode,
+                    "decode" => Command::Decode,
+                    "verify" => Command::Verify,
+                    "repair" => Command::Repair,
+                    other => return Err(format!("unknown command `{}`", other)),
+                })
+            }
+            _ if input.is_none() => input = Some(arg),
+            _ => return Err(format!("unexpected argument `{}`", arg)),
+        }
+    }
+    let command = command.ok_or("missing command")?;
+    if command != Command::Encode && (backend.is_some() || block_size.is_some()) {
+        return Err(
+            "the backend and block size are read from the frame, and only apply to encode".into(),
+        );
+    }
+    if command == Command::Verify && output.is_some() {
+        return Err("verify writes nothing".into());
+    }
+    Ok(Some(Options {
+        command,
+        input: input.filter(|input| input != "-"),
+        output: output.filter(|output| output != "-"),
+        backend: backend.unwrap_or(Backend::Auto),
+        block_size,
+        quiet,
+    }))
+}
+
+fn build_codec(backend: Backend, block_size: Option<usize>) -> Result<secded::SECDED, String> {
+    let kind = match backend {
+        Backend::Kind(kind) => kind,
+        Backend::Auto => match block_size.unwrap_or(120) {
+            0..=57 => CodecKind::U64,
+            58..=120 => CodecKind::U128,
+            _ => CodecKind::Dynamic,
+        },
+    };
+    let block_size = block_size.unwrap_or(match kind {
+        CodecKind::U64 => 57,
+        _ => 120,
+    });
+    let codec = kind.codec(block_size).ok_or_else(|| match kind {
+        CodecKind::Dynamic if cfg!(not(feature = "dyn")) => {
+            "the dyn backend requires the `dyn` feature".to_string()
+        }
+        _ => format!(
+            "the {:?} backend can't encode {} bits blocks",
+            kind, block_size
+        ),
+    })?;
+    if BlockLayout::of(&codec).payload_size == 0 {
+        return Err(format!(
+            "{} bits blocks can't carry a byte of payload",
+            block_size
+        ));
+    }
+    Ok(codec)
+}
+
+fn describe(error: &FrameError) -> String {
+    match error {
+        FrameError::Truncated => "the frame is truncated".into(),
+        FrameError::CorruptedHeader => "the frame's header is uncorrectable".into(),
+        FrameError::BadMagic => "the input isn't a frame".into(),
+        FrameError::UnsupportedVersion(version) => {
+            format!("frames of version {} aren't supported", version)
+        }
+        FrameError::UnsupportedCodec {
+            kind,
+            encodable_size,
+        } => format!(
+            "the frame's codec (kind {}, {} bits blocks) isn't supported by this build",
+            kind, encodable_size
+        ),
+        FrameError::FingerprintMismatch { expected, found } => format!(
+            "the frame's codec has fingerprint {:016x}, but this build's has {:016x}",
+            expected, found
+        ),
+        FrameError::Uncorrectable(blocks) => format!("{} uncorrectable blocks", blocks.len()),
+    }
+}
+
+/// A block that didn't decode cleanly.
+#[derive(Debug, PartialEq, Eq)]
+struct Damage {
+    block: usize,
+    /// The offset of the block in the frame, in bytes.
+    offset: usize,
+    /// The corrected bits, counted from the right-most bit of the block, or `None` if uncorrectable.
+    corrected: Option<Vec<usize>>,
+}
+
+/// The result of decoding each block of a frame.
+struct Scan {
+    block_count: usize,
+    /// Whether the header had errors, all of which were corrected.
+    header_corrected: bool,
+    damages: Vec<Damage>,
+    /// The original data, in which uncorrectable blocks are left as received.
+    data: Vec<u8>,
+    /// The frame, with its header and each correctable block re-encoded.
+    repaired: Vec<u8>,
+}
+
+impl Scan {
+    fn uncorrectable(&self) -> usize {
+        self.damages
+            .iter()
+            .filter(|damage| damage.corrected.is_none())
+            .count()
+    }
+}
+
+fn scan(frame: &[u8]) -> Result<Scan, FrameError> {
+    let decoder = FrameDecoder::new(frame)?;
+    let header = decoder.header().encode();
+    let block_count = decoder.header().block_count as usize;
+    let mut scan = Scan {
+        block_count,
+        header_corrected: frame[..FrameHeader::ENCODED_SIZE] != header[..],
+        damages: Vec::new(),
+        data: Vec::with_capacity(decoder.header().length as usize),
+        repaired: Vec::with_capacity(
+            FrameHeader::ENCODED_SIZE + block_count * decoder.layout().word_size,
+        ),
+    };
+    scan.repaired.extend_from_slice(&header);
+    for block in decoder.blocks() {
+        let corrected = match block.correction {
+            Ok(Correction::None) => None,
+            Ok(correction) => Some(Some(correction.bits())),
+            Err(_) => Some(None),
+        };
+        if let Some(corrected) = corrected {
+            scan.damages.push(Damage {
+                block: block.index,
+                offset: block.offset,
+                corrected,
+            });
+        }
+        scan.data.extend_from_slice(&block.data);
+        scan.repaired.extend_from_slice(&block.repaired);
+    }
+    Ok(scan)
+}
+
+fn report(scan: &Scan) {
+    if scan.header_corrected {
+        eprintln!("header: corrected");
+    }
+    for damage in scan.damages.iter() {
+        match &damage.corrected {
+            Some(bits) => eprintln!(
+                "block {} at offset {}: corrected bits {:?}",
+                damage.block, damage.offset, bits
+            ),
+            None => eprintln!(
+                "block {} at offset {}: uncorrectable",
+                damage.block, damage.offset
+            ),
+        }
+    }
+    let uncorrectable = scan.uncorrectable();
+    eprintln!(
+        "{} blocks, {} corrected, {} uncorrectable",
+        scan.block_count,
+        scan.damages.len() - uncorrectable,
+        uncorrectable
+    );
+}
+
+fn read(input: &Option<String>) -> Result<Vec<u8>, String> {
+    let mut data = Vec::new();
+    match input {
+        Some(path) => {
+            data = std::fs::read(path).map_err(|e| format!("can't read `{}`: {}", path, e))?
+        }
+        None => {
+            std::io::stdin()
+                .read_to_end(&mut data)
+                .map_err(|e| format!("can't read stdin: {}", e))?;
+        }
+    }
+    Ok(data)
+}
+
+fn write(output: &Option<String>, data: &[u8]) -> Result<(), String> {
+    match output {
+        Some(path) => {
+            std::fs::write(path, data).map_err(|e| format!("can't write `{}`: {}", path, e))
+        }
+        None => std::io::stdout()
+            .lock()
+            .write_all(data)
+            .map_err(|e| format!("can't write stdout: {}", e)),
+    }
+}
+
+/// Runs the tool, returning its exit code.
+fn run(options: &Options) -> Result<i32, String> {
+    let input = read(&options.input)?;
+    if options.command == Command::Encode {
+        let encoder = FrameEncoder::new(build_codec(options.backend, options.block_size)?);
+        write(&options.output, &encoder.encode(&input))?;
+        return Ok(0);
+    }
+    let scan = scan(&input).map_err(|e| describe(&e))?;
+    if !options.quiet {
+        report(&scan);
+    }
+    let uncorrectable = scan.uncorrectable() > 0;
+    match options.command {
+        Command::Decode if !uncorrectable => write(&options.output, &scan.data)?,
+        Command::Repair => write(&options.output, &scan.repaired)?,
+        _ => {}
+    }
+    Ok(if uncorrectable { 2 } else { 0 })
+}
+
+fn main() {
+    let code = match parse_args(std::env::args().skip(1)) {
+        Ok(None) => {
+            println!("{}", USAGE);
+            0
+        }
+        Ok(Some(options)) => run(&options).unwrap_or_else(|e| {
+            eprintln!("secded: {}", e);
+            1
+        }),
+        Err(e) => {
+            eprintln!("secded: {}\n\n{}", e, USAGE);
+            1
+        }
+    };
+    std::process::exit(code)
+}
+
+#[test]
+fn arguments() {
+    use secded::SecDedCodec;
+    let parse = |args: &str| parse_args(args.split_whitespace().map(String::from));
+    assert_eq!(
+        parse("encode -b 64 -s 26 data.bin -o -"),
+        Ok(Some(Options {
+            command: Command::Encode,
+            input: Some("data.bin".into()),
+            output: None,
+            backend: Backend::Kind(CodecKind::U64),
+            block_size: Some(26),
+            quiet: false,
+        }))
+    );
+    assert_eq!(parse("verify -q --help"), Ok(None));
+    assert!(parse("decode -s 57").is_err());
+    assert!(parse("encode -b 32").is_err());
+    assert!(parse("-q").is_err());
+    assert!(build_codec(Backend::Kind(CodecKind::U64), Some(120)).is_err());
+    assert!(build_codec(Backend::Auto, Some(3)).is_err());
+    assert_eq!(
+        build_codec(Backend::Auto, None).unwrap().encodable_size(),
+        120
+    );
+}
+
+#[test]
+fn damaged_frame() {
+    let data: Vec<u8> = (0..100u8).collect();
+    let encoder = FrameEncoder::new(build_codec(Backend::Kind(CodecKind::U64), None).unwrap());
+    let mut frame = encoder.encode(&data);
+    let offset = |block: usize| FrameHeader::ENCODED_SIZE + block * 8;
+    frame[1] ^= 4;
+    frame[offset(2) + 3] ^= 1;
+    frame[offset(5)] ^= 0b11;
+    let scanned = scan(&frame).unwrap();
+    assert!(scanned.header_corrected);
+    assert_eq!(scanned.block_count, 15);
+    assert_eq!(scanned.damages[0].offset, offset(2));
+    assert_eq!(scanned.damages[0].corrected, Some(vec![32]));
+    assert_eq!(scanned.damages[1].corrected, None);
+    assert_eq!(scanned.uncorrectable(), 1);
+    assert_eq!(scanned.data[..35], data[..35]);
+    assert_eq!(scanned.data[42..], data[42..]);
+    let rescanned = scan(&scanned.repaired).unwrap();
+    assert!(!rescanned.header_corrected);
+    assert_eq!(rescanned.damages.len(), 1);
+    frame[offset(5)] ^= 0b11;
+    assert_eq!(scan(&frame).unwrap().data, data);
+    let forged = FrameHeader {
+        length: 7 << 61,
+        block_count: 1 << 61,
+        ..encoder.header(0)
+    };
+    assert_eq!(
+        scan(&forged.encode()).err().map(|e| describe(&e)),
+        Some("the frame is truncated".to_string())
+    );
+}
@@ -0,0 +1,118 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::fault::flip_bit;
+use crate::*;
+
+/// The cheapest scheme: a single even parity bit, stored in the right-most bit of the slice.
+///
+/// It corrects nothing. It detects any odd number of errors, and no even number of errors.
+/// Decoding returns `Err(Uncorrectable { syndrome: 1 })` when it detects an error.
+///
+/// With `decode_with_erasures`, it only recovers a single erasure, and only when no other error occurred:
+/// any other error goes undetected, and makes it fill the erasure wrong. 2 erasures or more are always
+/// ambiguous, and reported as `ErasureError::Uncorrectable`.
+pub struct Parity {
+    encodable_size: usize,
+}
+
+impl Parity {
+    pub fn new(encodable_size: usize) -> Self {
+        Parity { encodable_size }
+    }
+
+    fn parity(&self, data: &[u8]) -> u8 {
+        (0..=self.encodable_size).fold(0, |parity, bit| parity ^ nth_bit_from_right(data, bit))
+    }
+}
+
+impl SecDedCodec for Parity {
+    fn encodable_size(&self) -> usize {
+        self.encodable_size
+    }
+    fn code_size(&self) -> usize {
+        1
+    }
+
+    /// Encodes the data IN-PLACE
+    /// # Arguments:
+    /// * `data`: The slice of data to encode. The right-most bit MUST be set to 0.
+    /// # Panics:
+    /// Panics if `data` is shorter than `encodable_size() + 1` bits.
+    fn encode(&self, data: &mut [u8]) {
+        if self.parity(data) == 1 {
+            flip_bit(data, 0);
+        }
+    }
+
+    /// Decodes the data IN-PLACE
+    /// The right-most bit is reset to 0 if no error was detected.
+    /// # Panics:
+    /// Panics if `data` is shorter than `encodable_size() + 1` bits.
+    fn decode(&self, data: &mut [u8]) -> Result<(), ()> {
+        match self.decode_with_correction(data) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(()),
+        }
+    }
+
+    fn decode_with_correction(&self, data: &mut [u8]) -> Result<Correction, Uncorrectable> {
+        if self.parity(data) == 1 {
+            return Err(Uncorrectable { syndrome: 1 });
+        }
+        if nth_bit_from_right(data, 0) == 1 {
+            flip_bit(data, 0);
+        }
+        Ok(Correction::None)
+    }
+}
+
+#[test]
+fn parity() {
+    let parity = Parity::new(20);
+    let expected = [0x0a, 0xbc, 0xde];
+    let mut encoded = expected;
+    parity.encode(&mut encoded);
+    assert_eq!(encoded, [0x0a, 0xbc, 0xdf]);
+    let mut buffer = encoded;
+    assert_eq!(parity.decode(&mut buffer), Ok(()));
+    assert_eq!(buffer, expected);
+    for i in 0..21 {
+        let mut buffer = encoded;
+        flip_bit(&mut buffer, i);
+        assert!(parity.decode(&mut buffer).is_err());
+        for j in (i + 1)..21 {
+            let mut buffer = encoded;
+            flip_bit(&mut buffer, i);
+            flip_bit(&mut buffer, j);
+            assert_eq!(parity.decode(&mut buffer), Ok(()));
+        }
+    }
+}
+
+#[test]
+fn erasures() {
+    use crate::erasure::ErasureError;
+    let parity = Parity::new(20);
+    let expected = [0x0a, 0xbc, 0xde];
+    let mut encoded = expected;
+    parity.encode(&mut encoded);
+    for i in 0..21 {
+        let mut buffer = encoded;
+        flip_bit(&mut buffer, i);
+        assert_eq!(parity.decode_with_erasures(&mut buffer, &[i]), Ok(vec![i]));
+        assert_eq!(buffer, expected);
+        for j in (i + 1)..21 {
+            let mut buffer = encoded;
+            assert_eq!(
+                parity.decode_with_erasures(&mut buffer, &[i, j]),
+                Err(ErasureError::Uncorrectable)
+            );
+            // Another error goes undetected, and the erasure is filled wrong.
+            flip_bit(&mut buffer, j);
+            assert_eq!(parity.decode_with_erasures(&mut buffer, &[i]), Ok(vec![i]));
+            assert_ne!(buffer, expected);
+        }
+    }
+}
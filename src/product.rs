@@ -0,0 +1,290 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Product codes: a 2D block of bits in which every row is a codeword of a row codec, and every column a
+//! codeword of a column codec.
+//!
+//! A block is a sequence of `row_count()` rows, each stored on `row_size()` bytes like any codeword of the
+//! row codec. Column codeword `j` is made of bit `j` of each row: row `i` holds the `i`th bit of each
+//! column, counted from the right-most bit. The first `columns.code_size()` rows thus hold the column
+//! codes, and the other ones the payload.
+//!
+//! Decoding alternates row and column passes, so that errors one dimension can't correct are corrected by
+//! the other one: any pattern of errors whose rows or columns each contain a single error is corrected.
+
+use crate::fault::flip_bit;
+use crate::frame::BlockLayout;
+use crate::*;
+
+/// A successful decoding's report, see `ProductCode::decode`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProductReport {
+    /// The `(row, bit)` coordinates of the corrected bits, in the order they were corrected.
+    pub corrected: Vec<(usize, usize)>,
+    /// The number of row and column passes needed.
+    pub passes: usize,
+}
+
+/// Returned by `ProductCode::decode` when some rows or columns still contain errors after the last pass.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProductError {
+    pub rows: Vec<usize>,
+    pub columns: Vec<usize>,
+}
+
+pub struct ProductCode<R, C> {
+    rows: R,
+    columns: C,
+    row_size: usize,
+    column_size: usize,
+    max_passes: usize,
+}
+
+impl<R: SecDedCodec, C: SecDedCodec> ProductCode<R, C> {
+    /// Builds a product code whose blocks carry `columns.encodable_size()` payload rows of
+    /// `rows.encodable_size()` bits.
+    pub fn new(rows: R, columns: C) -> Self {
+        let row_size = BlockLayout::of(&rows).word_size;
+        let column_size = BlockLayout::of(&columns).word_size;
+        ProductCode {
+            rows,
+            columns,
+            row_size,
+            column_size,
+            max_passes: 8,
+        }
+    }
+
+    /// Sets the maximum number of passes `decode` may run, a row pass and a column pass counting for two.
+    /// Defaults to 8, and can't be lower than 2.
+    pub fn max_passes(mut self, passes: usize) -> Self {
+        self.max_passes = passes.max(2);
+        self
+    }
+
+    pub fn rows(&self) -> &R {
+        &self.rows
+    }
+
+    pub fn columns(&self) -> &C {
+        &self.columns
+    }
+
+    /// Returns the number of bytes each row takes.
+    pub fn row_size(&self) -> usize {
+        self.row_size
+    }
+
+    /// Returns the number of rows in a block.
+    pub fn row_count(&self) -> usize {
+        self.columns.encodable_size() + self.columns.code_size()
+    }
+
+    /// Returns the size of a block, in bytes.
+    pub fn block_size(&self) -> usize {
+        self.row_count() * self.row_size
+    }
+
+    /// Returns the rows of `block` which carry the payload.
+    /// # Panics:
+    /// Panics if `block.len() != self.block_size()`
+    pub fn payload_rows<'a>(&self, block: &'a [u8]) -> impl Iterator<Item = &'a [u8]> {
+        self.check_size(block);
+        block[self.columns.code_size() * self.row_size..].chunks(self.row_size)
+    }
+
+    fn check_size(&self, block: &[u8]) {
+        if block.len() != self.block_size() {
+            panic!(
+                "A block of this product code takes {} bytes, not {}",
+                self.block_size(),
+                block.len()
+            );
+        }
+    }
+
+    fn row_width(&self) -> usize {
+        self.rows.encodable_size() + self.rows.code_size()
+    }
+
+    fn read_column(&self, block: &[u8], column: usize, buffer: &mut [u8]) {
+        for x in buffer.iter_mut() {
+            *x = 0;
+        }
+        for (i, row) in block.chunks(self.row_size).enumerate() {
+            if nth_bit_from_right(row, column) == 1 {
+                flip_bit(buffer, i);
+            }
+        }
+    }
+
+    /// Encodes the block IN-PLACE
+    /// # Arguments:
+    /// * `block`: The block to encode: the payload rows must hold their payload, with their last
+    ///   `rows.code_size()` bits set to 0, and the first `columns.code_size()` rows MUST be set to 0.
+    /// # Panics:
+    /// Panics if `block.len() != self.block_size()`, and whenever the codecs' `encode` would.
+    pub fn encode(&self, block: &mut [u8]) {
+        self.check_size(block);
+        let code_rows = self.columns.code_size();
+        for row in block[code_rows * self.row_size..].chunks_mut(self.row_size) {
+            self.rows.encode(row);
+        }
+        let mut buffer = vec![0u8; self.column_size];
+        for column in 0..self.row_width() {
+            self.read_column(block, column, &mut buffer);
+            self.columns.encode(&mut buffer);
+            for i in 0..code_rows {
+                if nth_bit_from_right(&buffer, i) == 1 {
+                    flip_bit(
+                        &mut block[i * self.row_size..(i + 1) * self.row_size],
+                        column,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Lists the rows and columns of `block` that aren't valid codewords, without modifying it.
+    fn check(&self, block: &[u8]) -> ProductError {
+        let dirty = |codec: &dyn SecDedCodec, buffer: &mut [u8]| {
+            !matches!(codec.decode_with_correction(buffer), Ok(Correction::None))
+        };
+        let mut buffer = vec![0u8; self.row_size];
+        let rows = block
+            .chunks(self.row_size)
+            .enumerate()
+            .filter(|(_, row)| {
+                buffer.copy_from_slice(row);
+                dirty(&self.rows, &mut buffer)
+            })
+            .map(|(i, _)| i)
+            .collect();
+        let mut buffer = vec![0u8; self.column_size];
+        let columns = (0..self.row_width())
+            .filter(|&column| {
+                self.read_column(block, column, &mut buffer);
+                dirty(&self.columns, &mut buffer)
+            })
+            .collect();
+        ProductError { rows, columns }
+    }
+
+    /// Decodes the block IN-PLACE, alternating row and column passes until no error remains.
+    /// # Returns:
+    /// `Ok(report)` if every row and column is a valid codeword. The code rows and the last
+    /// `rows.code_size()` bits of each payload row are then reset to 0.
+    /// `Err(ProductError { .. })` listing the rows and columns that still contain errors once passes stop
+    /// making progress, or after the last pass. The block is left partially corrected.
+    /// # Panics:
+    /// Panics if `block.len() != self.block_size()`, and whenever the codecs' `decode` would.
+    pub fn decode(&self, block: &mut [u8]) -> Result<ProductReport, ProductError> {
+        self.check_size(block);
+        let mut report = ProductReport::default();
+        let mut row_buffer = vec![0u8; self.row_size];
+        let mut column_buffer = vec![0u8; self.column_size];
+        let mut stalled = 0;
+        while report.passes < self.max_passes {
+            let columns_pass = report.passes % 2 == 1;
+            report.passes += 1;
+            let mut corrected = 0;
+            if columns_pass {
+                for column in 0..self.row_width() {
+                    self.read_column(block, column, &mut column_buffer);
+                    if let Ok(correction) = self.columns.decode_with_correction(&mut column_buffer)
+                    {
+                        for row in correction.bits() {
+                            let row_bytes =
+                                &mut block[row * self.row_size..(row + 1) * self.row_size];
+                            flip_bit(row_bytes, column);
+                            report.corrected.push((row, column));
+                            corrected += 1;
+                        }
+                    }
+                }
+            } else {
+                for (i, row) in block.chunks_mut(self.row_size).enumerate() {
+                    row_buffer.copy_from_slice(row);
+                    if let Ok(correction) = self.rows.decode_with_correction(&mut row_buffer) {
+                        for bit in correction.bits() {
+                            flip_bit(row, bit);
+                            report.corrected.push((i, bit));
+                            corrected += 1;
+                        }
+                    }
+                }
+            }
+            if corrected == 0 {
+                stalled += 1;
+            } else {
+                stalled = 0;
+            }
+            // Two passes without correction mean that both dimensions have been checked on an unchanged block.
+            if stalled == 2 {
+                break;
+            }
+        }
+        let error = self.check(block);
+        if !error.rows.is_empty() || !error.columns.is_empty() {
+            return Err(error);
+        }
+        let code_rows = self.columns.code_size();
+        for x in block[..code_rows * self.row_size].iter_mut() {
+            *x = 0;
+        }
+        for row in block[code_rows * self.row_size..].chunks_mut(self.row_size) {
+            for bit in 0..self.rows.code_size() {
+                if nth_bit_from_right(row, bit) == 1 {
+                    flip_bit(row, bit);
+                }
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[test]
+fn product_code() {
+    let product = ProductCode::new(SecDed64::new(57), SecDed64::new(26));
+    assert_eq!((product.row_count(), product.block_size()), (32, 256));
+    let mut expected = vec![0u8; product.block_size()];
+    for (i, x) in expected.iter_mut().enumerate().skip(6 * 8) {
+        if i % 8 != 7 {
+            *x = (i * 37) as u8;
+        }
+    }
+    let mut encoded = expected.clone();
+    product.encode(&mut encoded);
+    for row in encoded.chunks(8) {
+        assert_eq!(product.rows().decode(&mut row.to_vec()), Ok(()));
+    }
+    // Rows 3 and 12 hold double errors, which the column pass corrects, and column 30 holds a double error,
+    // which the row pass corrects.
+    let mut block = encoded.clone();
+    for &(row, bit) in [(3, 10), (3, 50), (12, 0), (12, 63), (20, 30), (21, 30)].iter() {
+        flip_bit(&mut block[row * 8..(row + 1) * 8], bit);
+    }
+    let report = product.decode(&mut block).unwrap();
+    assert_eq!(report.corrected.len(), 6);
+    assert_eq!(block, expected);
+    assert_eq!(product.payload_rows(&block).count(), 26);
+    // A single error is corrected by the first pass, and checked even when no pass may confirm it.
+    let mut block = encoded.clone();
+    flip_bit(&mut block[9 * 8..10 * 8], 20);
+    let short = ProductCode::new(SecDed64::new(57), SecDed64::new(26)).max_passes(2);
+    assert_eq!(short.decode(&mut block).unwrap().corrected, vec![(9, 20)]);
+    assert_eq!(block, expected);
+    // Errors on the corners of a rectangle are detected by both dimensions.
+    let mut block = encoded;
+    for &(row, bit) in [(7, 4), (7, 40), (15, 4), (15, 40)].iter() {
+        flip_bit(&mut block[row * 8..(row + 1) * 8], bit);
+    }
+    assert_eq!(
+        product.decode(&mut block),
+        Err(ProductError {
+            rows: vec![7, 15],
+            columns: vec![4, 40]
+        })
+    );
+}
@@ -0,0 +1,189 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::*;
+use core::marker::PhantomData;
+
+lazy_static::lazy_static! {
+    pub(crate) static ref SECDED_64_57: SecDed64 = SecDed64::new(57);
+    pub(crate) static ref SECDED_128_120: SecDed128 = SecDed128::new(120);
+}
+
+/// Types that can be safely copied to and from raw bytes: they have no padding, and any bit pattern is a
+/// valid value.
+///
+/// # Safety
+/// Implementing this for types that don't respect these constraints is undefined behaviour.
+pub unsafe trait Pod: Copy {}
+
+unsafe impl Pod for u8 {}
+unsafe impl Pod for u16 {}
+unsafe impl Pod for u32 {}
+unsafe impl Pod for u64 {}
+unsafe impl Pod for usize {}
+unsafe impl Pod for i8 {}
+unsafe impl Pod for i16 {}
+unsafe impl Pod for i32 {}
+unsafe impl Pod for i64 {}
+unsafe impl Pod for isize {}
+unsafe impl Pod for f32 {}
+unsafe impl Pod for f64 {}
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+
+/// The largest `Pod` that can be stored in a single codeword, see `Protected`.
+pub const MAX_PROTECTED_SIZE: usize = 15;
+
+/// Writes `value`'s bytes into the first `size_of::<T>()` bytes of `buffer`.
+pub(crate) fn write_pod<T: Pod>(value: &T, buffer: &mut [u8]) {
+    let size = core::mem::size_of::<T>();
+    assert!(buffer.len() >= size);
+    unsafe {
+        core::ptr::copy_nonoverlapping(value as *const T as *const u8, buffer.as_mut_ptr(), size)
+    }
+}
+
+/// Reads a `T` from the first `size_of::<T>()` bytes of `buffer`.
+pub(crate) fn read_pod<T: Pod>(buffer: &[u8]) -> T {
+    assert!(buffer.len() >= core::mem::size_of::<T>());
+    unsafe { core::ptr::read_unaligned(buffer.as_ptr() as *const T) }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Codeword {
+    U64([u8; 8]),
+    U128([u8; 16]),
+}
+
+impl Codeword {
+    /// Returns an empty codeword of the smallest backend able to hold `size` bytes in its leading bytes.
+    pub(crate) fn for_size(size: usize) -> Self {
+        match size {
+            0..=7 => Codeword::U64([0; 8]),
+            8..=MAX_PROTECTED_SIZE => Codeword::U128([0; 16]),
+            _ => panic!(
+                "{} bytes don't fit in a single codeword, the maximum is {} bytes",
+                size, MAX_PROTECTED_SIZE
+            ),
+        }
+    }
+
+    pub(crate) fn codec(&self) -> &'static dyn SecDedCodec {
+        match self {
+            Codeword::U64(_) => &*SECDED_64_57,
+            Codeword::U128(_) => &*SECDED_128_120,
+        }
+    }
+
+    pub(crate) fn bytes(&self) -> &[u8] {
+        match self {
+            Codeword::U64(bytes) => bytes,
+            Codeword::U128(bytes) => bytes,
+        }
+    }
+
+    pub(crate) fn bytes_mut(&mut self) -> &mut [u8] {
+        match self {
+            Codeword::U64(bytes) => bytes,
+            Codeword::U128(bytes) => bytes,
+        }
+    }
+
+    pub(crate) fn encode<T: Pod>(&mut self, value: &T) {
+        let codec = self.codec();
+        let bytes = self.bytes_mut();
+        for x in bytes.iter_mut() {
+            *x = 0;
+        }
+        write_pod(value, bytes);
+        codec.encode(bytes);
+    }
+
+    /// Decodes a copy of the codeword, leaving it untouched.
+    pub(crate) fn decode<T: Pod>(&self) -> Result<(T, Correction), Uncorrectable> {
+        let mut copy = *self;
+        let correction = self.codec().decode_with_correction(copy.bytes_mut())?;
+        Ok((read_pod(copy.bytes()), correction))
+    }
+}
+
+/// A value stored as a SECDED codeword, using `SecDed64` for values of up to 7 bytes and `SecDed128` for
+/// values of up to `MAX_PROTECTED_SIZE` bytes.
+///
+/// The value is decoded on each `get`, and re-encoded on each `set`. Since `get` only reads the codeword,
+/// call `check_and_repair` periodically to write corrections back and avoid accumulating errors.
+#[derive(Clone, Copy)]
+pub struct Protected<T: Pod> {
+    codeword: Codeword,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Pod> Protected<T> {
+    /// # Panics:
+    /// Panics if `size_of::<T>() > MAX_PROTECTED_SIZE`
+    pub fn new(value: T) -> Self {
+        let mut codeword = Codeword::for_size(core::mem::size_of::<T>());
+        codeword.encode(&value);
+        Protected {
+            codeword,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the stored value, with a single error corrected implicitly.
+    /// `Err(())` if 2 errors were detected.
+    #[allow(clippy::result_unit_err)]
+    pub fn get(&self) -> Result<T, ()> {
+        match self.codeword.decode() {
+            Ok((value, _)) => Ok(value),
+            Err(_) => Err(()),
+        }
+    }
+
+    pub fn set(&mut self, value: T) {
+        self.codeword.encode(&value);
+    }
+
+    /// Decodes the stored value, and re-encodes it if an error was corrected.
+    /// The codeword is left untouched if 2 errors were detected.
+    pub fn check_and_repair(&mut self) -> Result<Correction, Uncorrectable> {
+        let (value, correction) = self.codeword.decode::<T>()?;
+        if let Correction::Bit { .. } = correction {
+            self.codeword.encode(&value);
+        }
+        Ok(correction)
+    }
+
+    /// Returns the codeword in which the value is stored.
+    pub fn codeword(&self) -> &[u8] {
+        self.codeword.bytes()
+    }
+}
+
+#[test]
+fn protected() {
+    let mut counter = Protected::new(0xdead_beefu32);
+    assert_eq!(counter.codeword().len(), 8);
+    assert_eq!(counter.get(), Ok(0xdead_beef));
+    counter.set(42);
+    for i in 0..64 {
+        let mut local = counter;
+        local.codeword.bytes_mut()[i / 8] ^= 1 << (i % 8);
+        assert_eq!(local.get(), Ok(42));
+        assert!(matches!(
+            local.check_and_repair(),
+            Ok(Correction::Bit { .. })
+        ));
+        assert_eq!(local.codeword(), counter.codeword());
+        assert_eq!(local.check_and_repair(), Ok(Correction::None));
+    }
+    counter.codeword.bytes_mut()[2] ^= 0b11;
+    assert_eq!(counter.get(), Err(()));
+    assert!(counter.check_and_repair().is_err());
+
+    let pointer = Protected::new(0x7fff_1234_5678_9abcu64);
+    assert_eq!(pointer.codeword().len(), 16);
+    assert_eq!(pointer.get(), Ok(0x7fff_1234_5678_9abc));
+    let bytes = Protected::new([1u8, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+    assert_eq!(bytes.get().unwrap()[14], 15);
+}
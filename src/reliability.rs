@@ -0,0 +1,228 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Closed-form reliability of a codec over a binary symmetric channel.
+//!
+//! For an error pattern `e`, let `s(e)` be the code that encoding the data part of `e` would produce, XORed
+//! with the code part of `e`: `s` is a linear image of the decoder's syndrome, and only depends on the codec's
+//! `H` matrix. Patterns of weight 0 or 1 are always decoded correctly. Any heavier pattern whose syndrome is
+//! null or equal to a single bit's is silently miscorrected, and all others are detected.
+
+use crate::*;
+
+/// The probabilities of each outcome when decoding a codeword.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reliability {
+    /// The codeword had at most one error, and was decoded correctly.
+    pub correct: f64,
+    /// The codeword had at least 2 errors, which were detected.
+    pub detected: f64,
+    /// The codeword had at least 2 errors, and was decoded to the wrong data without any error reported.
+    pub miscorrected: f64,
+}
+
+/// Which failures count towards the target of `suggest_encodable_size`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Residual {
+    /// Only silent miscorrections.
+    Undetected,
+    /// Both detected failures and silent miscorrections.
+    Uncorrected,
+}
+
+impl Reliability {
+    pub fn residual(&self, residual: Residual) -> f64 {
+        match residual {
+            Residual::Undetected => self.miscorrected,
+            Residual::Uncorrected => self.detected + self.miscorrected,
+        }
+    }
+}
+
+/// The columns of a codec's `H` matrix, from which its reliability can be computed exactly.
+pub struct ReliabilityModel {
+    encodable_size: usize,
+    code_size: usize,
+    /// `s(e)` for each single bit error, counted from the right-most bit of the codeword.
+    columns: Vec<u64>,
+}
+
+impl ReliabilityModel {
+    /// # Panics:
+    /// Panics if `codec.code_size() > 24`, which would make the model too big to compute.
+    pub fn new<C: SecDedCodec + ?Sized>(codec: &C) -> Self {
+        let (encodable_size, code_size) = (codec.encodable_size(), codec.code_size());
+        if code_size > 24 {
+            panic!(
+                "A code of {} bits is too big for the reliability model",
+                code_size
+            );
+        }
+        ReliabilityModel {
+            encodable_size,
+            code_size,
+            columns: syndrome_columns(codec),
+        }
+    }
+
+    pub fn encodable_size(&self) -> usize {
+        self.encodable_size
+    }
+
+    pub fn code_size(&self) -> usize {
+        self.code_size
+    }
+
+    /// Returns `true` for the syndromes that the decoder accepts: the null one, and each single bit's.
+    fn accepted(&self) -> Vec<bool> {
+        let mut accepted = vec![false; 1 << self.code_size];
+        accepted[0] = true;
+        for &column in self.columns.iter() {
+            accepted[column as usize] = true;
+        }
+        accepted
+    }
+
+    /// Computes the exact probabilities of each outcome when each bit of the codeword is flipped
+    /// independently with probability `p`.
+    pub fn reliability(&self, p: f64) -> Reliability {
+        // The syndromes' distributions of patterns of weight 0, 1, and at least 2, kept apart to avoid
+        // cancellations when `p` is small.
+        let syndromes = 1 << self.code_size;
+        let mut none = vec![0f64; syndromes];
+        let mut single = vec![0f64; syndromes];
+        let mut multiple = vec![0f64; syndromes];
+        none[0] = 1.0;
+        for &column in self.columns.iter() {
+            let column = column as usize;
+            for s in 0..syndromes {
+                let t = s ^ column;
+                if s < t {
+                    let (m_s, m_t) = (multiple[s], multiple[t]);
+                    multiple[s] = (1.0 - p) * m_s + p * (m_t + single[t]);
+                    multiple[t] = (1.0 - p) * m_t + p * (m_s + single[s]);
+                    let (s_s, s_t) = (single[s], single[t]);
+                    single[s] = (1.0 - p) * s_s + p * none[t];
+                    single[t] = (1.0 - p) * s_t + p * none[s];
+                    let (n_s, n_t) = (none[s], none[t]);
+                    none[s] = (1.0 - p) * n_s;
+                    none[t] = (1.0 - p) * n_t;
+                }
+            }
+        }
+        let accepted = self.accepted();
+        let (mut detected, mut miscorrected) = (0.0, 0.0);
+        for (x, accepted) in multiple.iter().zip(accepted.iter()) {
+            if *accepted {
+                miscorrected += x;
+            } else {
+                detected += x;
+            }
+        }
+        Reliability {
+            correct: none.iter().sum::<f64>() + single.iter().sum::<f64>(),
+            detected,
+            miscorrected,
+        }
+    }
+
+    /// Returns, for each weight `w <= max_weight`, the number of error patterns of weight `w` that are
+    /// silently miscorrected.
+    pub fn miscorrection_weights(&self, max_weight: usize) -> Vec<f64> {
+        let syndromes = 1 << self.code_size;
+        let mut counts = vec![vec![0f64; syndromes]; max_weight + 1];
+        counts[0][0] = 1.0;
+        for &column in self.columns.iter() {
+            for w in (1..=max_weight).rev() {
+                let (lighter, heavier) = counts.split_at_mut(w);
+                for (s, x) in heavier[0].iter_mut().enumerate() {
+                    *x += lighter[w - 1][s ^ column as usize];
+                }
+            }
+        }
+        let accepted = self.accepted();
+        counts
+            .iter()
+            .enumerate()
+            .map(|(w, counts)| match w {
+                0 | 1 => 0.0,
+                _ => counts
+                    .iter()
+                    .zip(accepted.iter())
+                    .filter(|(_, accepted)| **accepted)
+                    .map(|(x, _)| x)
+                    .sum(),
+            })
+            .collect()
+    }
+}
+
+/// Returns the smallest encodable size, from 1 up to `max_encodable_size`, for which a codeword built by
+/// `SECDED::new` fails with a probability of at most `target` when each bit is flipped with probability `p`.
+///
+/// Sizes are tried in increasing order, and the search stops at the first one that meets the target.
+/// Returns `None` if none of them does. Without the `dyn` feature, `max_encodable_size` is capped at 120.
+pub fn suggest_encodable_size(
+    p: f64,
+    target: f64,
+    residual: Residual,
+    max_encodable_size: usize,
+) -> Option<usize> {
+    #[cfg(not(feature = "dyn"))]
+    let max_encodable_size = max_encodable_size.min(120);
+    (1..=max_encodable_size).find(|&encodable_size| {
+        ReliabilityModel::new(&SECDED::new(encodable_size))
+            .reliability(p)
+            .residual(residual)
+            <= target
+    })
+}
+
+#[test]
+fn reliability() {
+    let model = ReliabilityModel::new(&SecDed64::new(57));
+    let weights = model.miscorrection_weights(4);
+    assert_eq!(weights[2], 0.0);
+    assert!(weights[3] > 0.0);
+    let reliability = model.reliability(1e-3);
+    let total = reliability.correct + reliability.detected + reliability.miscorrected;
+    assert!((total - 1.0).abs() < 1e-12);
+    let p: f64 = 1e-3;
+    let expected = (3..=4).map(|w| weights[w] * p.powi(w as i32) * (1.0 - p).powi(64 - w as i32));
+    let expected: f64 = expected.sum();
+    assert!((reliability.miscorrected - expected).abs() / expected < 1e-3);
+    let p: f64 = 1e-9;
+    let expected = weights[3] * p.powi(3);
+    let miscorrected = model.reliability(p).miscorrected;
+    assert!((miscorrected - expected).abs() / expected < 1e-6);
+}
+
+#[test]
+fn against_simulation() {
+    use crate::simulation::{BinarySymmetric, Simulation};
+    let p = 0.02;
+    let reliability = ReliabilityModel::new(&SecDed64::new(26)).reliability(p);
+    let point = Simulation::new(SecDed64::new(26), 20000)
+        .seed(3)
+        .run(p, BinarySymmetric { p });
+    assert!((point.detected_rate() - reliability.detected).abs() < 0.01);
+    assert!((point.miscorrected_rate() - reliability.miscorrected).abs() < 0.01);
+}
+
+#[test]
+fn suggestion() {
+    let size = suggest_encodable_size(1e-4, 1e-5, Residual::Uncorrected, 120).unwrap();
+    let fits = |size| {
+        ReliabilityModel::new(&SECDED::new(size))
+            .reliability(1e-4)
+            .residual(Residual::Uncorrected)
+            <= 1e-5
+    };
+    assert!(fits(size));
+    assert!((1..size).all(|size| !fits(size)));
+    assert_eq!(
+        suggest_encodable_size(0.1, 1e-9, Residual::Undetected, 57),
+        None
+    );
+}
@@ -0,0 +1,102 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! SEC-DED-SBD matrices: on top of single error correction and double error detection, any error confined
+//! to one byte of the codeword is detected, and never miscorrected.
+//!
+//! With `H` extended by the overall parity row, this requires that no subset of at least 2 columns of a
+//! byte sums to 0 or to another column. Columns are chosen greedily, byte after byte, among the values of
+//! weight 2 or more, which needs a few more code bits than the plain construction.
+
+use crate::*;
+
+/// Returns the subset sums of `columns`, along with the parity of the subsets' sizes.
+fn subset_sums(columns: &[u16]) -> Vec<(u16, bool)> {
+    let mut sums = vec![(0, false)];
+    for &column in columns {
+        for i in 0..sums.len() {
+            let (sum, odd) = sums[i];
+            sums.push((sum ^ column, !odd));
+        }
+    }
+    sums
+}
+
+/// Searches for the data columns of a SEC-DED-SBD matrix with `m` rows, for the `encodable_size` data
+/// bits that follow the overall parity bit and the `m` check bits.
+/// The columns use the same encoding as the data columns of `SecDed64::new`.
+fn columns_for(encodable_size: usize, m: usize) -> Option<Vec<u16>> {
+    // Columns are handled extended with the parity row, as `h << 1 | 1`.
+    let mut columns: Vec<u16> = vec![1];
+    columns.extend((1..=m).map(|p| (1 << (p - 1)) << 1 | 1));
+    let mut used = vec![false; 1 << (m + 1)];
+    let mut forbidden = vec![false; 1 << (m + 1)];
+    for &column in columns.iter() {
+        used[column as usize] = true;
+    }
+    let forbid_byte = |forbidden: &mut Vec<bool>, byte: &[u16]| {
+        for (sum, odd) in subset_sums(byte) {
+            forbidden[sum as usize] |= odd;
+        }
+    };
+    for byte in columns.chunks_exact(8) {
+        forbid_byte(&mut forbidden, byte);
+    }
+    let total_size = encodable_size + m + 1;
+    while columns.len() < total_size {
+        let start = columns.len() / 8 * 8;
+        let sums = subset_sums(&columns[start..]);
+        // Any new column must keep the byte's columns independent, and keep the sums of an odd number of
+        // them from matching any other column.
+        let mut rejected = forbidden.clone();
+        for &(sum, odd) in sums.iter() {
+            rejected[sum as usize] = true;
+            if !odd {
+                for &column in columns.iter() {
+                    rejected[(column ^ sum) as usize] = true;
+                }
+            }
+        }
+        let column = (1..(1u16 << m))
+            .filter(|h| h.count_ones() >= 2)
+            .map(|h| h << 1 | 1)
+            .find(|&column| !used[column as usize] && !rejected[column as usize])?;
+        used[column as usize] = true;
+        columns.push(column);
+        if columns.len() % 8 == 0 {
+            forbid_byte(&mut forbidden, &columns[start..]);
+        }
+    }
+    Some(columns[m + 1..].iter().map(|column| column >> 1).collect())
+}
+
+/// Returns the smallest number of rows `m`, at most `max_m`, for which a SEC-DED-SBD matrix of
+/// `encodable_size` data bits fits in `word_size` bits, along with its data columns.
+pub(crate) fn sbd_columns(
+    encodable_size: usize,
+    word_size: usize,
+    max_m: usize,
+) -> Option<(usize, Vec<u16>)> {
+    (hamming_size(encodable_size)..=max_m)
+        .take_while(|m| encodable_size + m < word_size)
+        .find_map(|m| columns_for(encodable_size, m).map(|columns| (m, columns)))
+}
+
+/// Panics unless every error of at least 2 bits confined to one byte of the codeword has a syndrome which is
+/// neither null nor a single bit's, `syndromes` holding the syndrome of each single bit error.
+pub(crate) fn assert_byte_errors_detected(syndromes: &[u16]) {
+    for (byte, columns) in syndromes.chunks(8).enumerate() {
+        for (mask, (sum, _)) in subset_sums(columns).into_iter().enumerate() {
+            if mask.count_ones() >= 2 {
+                assert_ne!(sum, 0, "bits {:#010b} of byte {}", mask, byte);
+                assert!(
+                    !syndromes.contains(&sum),
+                    "bits {:#010b} of byte {} would be miscorrected",
+                    mask,
+                    byte
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,164 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::frame::BlockLayout;
+use crate::*;
+
+/// The results of a full pass of a `Scrubber` over a region.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScrubReport {
+    pub words_scrubbed: usize,
+    /// Indices of the words in which a single error was corrected and written back.
+    pub corrected: Vec<usize>,
+    /// Indices of the words in which at least 2 errors were detected, these are left untouched.
+    pub uncorrectable: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScrubProgress {
+    /// The pass isn't over yet: `scrubbed` out of `total` words have been checked.
+    InProgress { scrubbed: usize, total: usize },
+    /// The pass is over, the next call to `Scrubber::step` will start a new one.
+    Done(ScrubReport),
+}
+
+/// Incrementally walks a region made of consecutive codewords, correcting single errors in place.
+///
+/// Each call to `step` checks at most `budget` words, which makes it suitable for idle hooks or low-priority
+/// background threads. The region must be passed to each `step` of a pass.
+pub struct Scrubber<C> {
+    codec: C,
+    word_size: usize,
+    position: usize,
+    report: ScrubReport,
+    buffer: Vec<u8>,
+}
+
+impl<C: SecDedCodec> Scrubber<C> {
+    pub fn new(codec: C) -> Self {
+        let word_size = BlockLayout::of(&codec).word_size;
+        Scrubber {
+            codec,
+            word_size,
+            position: 0,
+            report: ScrubReport::default(),
+            buffer: vec![0; word_size],
+        }
+    }
+
+    pub fn codec(&self) -> &C {
+        &self.codec
+    }
+
+    pub fn word_size(&self) -> usize {
+        self.word_size
+    }
+
+    /// Returns the index of the next word to be scrubbed.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the results of the current pass so far.
+    pub fn report(&self) -> &ScrubReport {
+        &self.report
+    }
+
+    /// Abandons the current pass.
+    pub fn restart(&mut self) {
+        self.position = 0;
+        self.report = ScrubReport::default();
+    }
+
+    /// Scrubs at most `budget` words of `region`, starting where the previous step stopped.
+    /// # Panics:
+    /// Panics if `region.len()` isn't a multiple of the codec's word size.
+    pub fn step(&mut self, region: &mut [u8], budget: usize) -> ScrubProgress {
+        if region.len() % self.word_size != 0 {
+            panic!(
+                "A region of {} bytes can't be made of {} bytes long codewords",
+                region.len(),
+                self.word_size
+            );
+        }
+        let total = region.len() / self.word_size;
+        let end = total.min(self.position.saturating_add(budget));
+        for index in self.position..end {
+            let word = &mut region[index * self.word_size..(index + 1) * self.word_size];
+            self.buffer.copy_from_slice(word);
+            match self.codec.decode_with_correction(&mut self.buffer) {
+                Ok(Correction::None) => {}
+                Ok(_) => {
+                    self.codec.encode(&mut self.buffer);
+                    word.copy_from_slice(&self.buffer);
+                    self.report.corrected.push(index);
+                }
+                Err(_) => self.report.uncorrectable.push(index),
+            }
+            self.report.words_scrubbed += 1;
+        }
+        self.position = end;
+        if self.position < total {
+            ScrubProgress::InProgress {
+                scrubbed: self.position,
+                total,
+            }
+        } else {
+            self.position = 0;
+            ScrubProgress::Done(std::mem::take(&mut self.report))
+        }
+    }
+
+    /// Scrubs the whole region at once.
+    pub fn scrub(&mut self, region: &mut [u8]) -> ScrubReport {
+        self.restart();
+        match self.step(region, usize::MAX) {
+            ScrubProgress::Done(report) => report,
+            ScrubProgress::InProgress { .. } => unreachable!(),
+        }
+    }
+}
+
+#[test]
+fn scrub() {
+    let secded = SecDed64::new(57);
+    let mut region = vec![0u8; 8 * 10];
+    for (i, word) in region.chunks_mut(8).enumerate() {
+        word[3] = i as u8;
+        secded.encode(word);
+    }
+    let expected = region.clone();
+    region[8 * 2 + 1] ^= 1 << 3;
+    region[8 * 5 + 7] ^= 1;
+    region[8 * 7] ^= 0b101;
+    let mut scrubber = Scrubber::new(secded);
+    assert_eq!(
+        scrubber.step(&mut region, 4),
+        ScrubProgress::InProgress {
+            scrubbed: 4,
+            total: 10
+        }
+    );
+    assert_eq!(scrubber.report().corrected, vec![2]);
+    assert_eq!(
+        scrubber.step(&mut region, 4),
+        ScrubProgress::InProgress {
+            scrubbed: 8,
+            total: 10
+        }
+    );
+    assert_eq!(
+        scrubber.step(&mut region, 4),
+        ScrubProgress::Done(ScrubReport {
+            words_scrubbed: 10,
+            corrected: vec![2, 5],
+            uncorrectable: vec![7],
+        })
+    );
+    assert_eq!(region[..8 * 7], expected[..8 * 7]);
+    assert_eq!(region[8 * 8..], expected[8 * 8..]);
+    let report = scrubber.scrub(&mut region);
+    assert_eq!(report.corrected, vec![]);
+    assert_eq!(report.uncorrectable, vec![7]);
+}
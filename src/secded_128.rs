@@ -8,8 +8,8 @@ use crate::*;
 pub struct SecDed128 {
     encodable_size: u8,
     m: u8,
-    mask: u8,
-    encode_matrix: [u128; 7],
+    mask: u16,
+    pub(crate) encode_matrix: [u128; 12],
     syndromes: [u16; 128],
 }
 
@@ -23,7 +23,7 @@ impl SecDed128 {
                 result <<= 1;
             }
         }
-        result |= result.parity();
+        result |= result.parity() ^ value.parity();
         result
     }
 
@@ -32,7 +32,7 @@ impl SecDed128 {
             panic!("This implementation is based on u64, and can thus only encode payloads of at most 57 bits");
         }
         let m = hamming_size(encodable_size);
-        let mut encode_matrix = [0; 7];
+        let mut encode_matrix = [0; 12];
         for i in 1..=(2_u128.pow(m as u32)) {
             if i.count() < 2 {
                 continue;
@@ -48,6 +48,35 @@ impl SecDed128 {
                 *x |= 1 << (m - i);
             }
         }
+        Self::from_matrix(encodable_size, m, encode_matrix)
+    }
+
+    /// Builds a SEC-DED-SBD codec: on top of correcting single errors and detecting double errors like
+    /// `new`'s, it detects any error confined to one byte of the slice, and never miscorrects it.
+    /// This takes a few more code bits, see `code_size()`.
+    /// # Panics:
+    /// Panics if `encodable_size > 115`
+    pub fn new_sbd(encodable_size: usize) -> Self {
+        let (m, columns) = match sbd::sbd_columns(encodable_size, 128, 12) {
+            Some(found) => found,
+            None => panic!(
+                "Can't build a SEC-DED-SBD code for {} bits payloads in 128 bits words",
+                encodable_size
+            ),
+        };
+        let mut encode_matrix = [0; 12];
+        for (k, x) in encode_matrix.iter_mut().enumerate().take(m) {
+            for (bit, column) in columns.iter().enumerate() {
+                *x |= u128::from(column >> (m - 1 - k) & 1) << (m + 1 + bit);
+            }
+            *x |= 1 << (m - k);
+        }
+        let secded = Self::from_matrix(encodable_size, m, encode_matrix);
+        sbd::assert_byte_errors_detected(&secded.syndromes[..=(encodable_size + m)]);
+        secded
+    }
+
+    fn from_matrix(encodable_size: usize, m: usize, encode_matrix: [u128; 12]) -> Self {
         let mut syndromes = [0; 128];
         for (error_bit, syndrome) in syndromes
             .iter_mut()
@@ -65,12 +94,36 @@ impl SecDed128 {
         SecDed128 {
             encodable_size: encodable_size as u8,
             m: m as u8,
-            mask: { (0..=m).map(|x| 1u8 << x).sum::<u8>() },
+            mask: { (0..=m).map(|x| 1u16 << x).sum::<u16>() },
             encode_matrix,
             syndromes,
         }
     }
 
+    /// Lists every pair of bits `(i, j)`, `i < j`, whose combined errors would produce the syndrome of
+    /// `buffer`, counting bits from the right-most bit of the slice.
+    ///
+    /// When `decode` returns `Err(())`, one of these pairs is the double error that was detected if no more than
+    /// 2 bits flipped, which lets higher layers resolve the ambiguity with outside information.
+    /// # Panics:
+    /// Panics if `buffer.len() != 16`
+    pub fn double_error_candidates<'a>(
+        &'a self,
+        buffer: &[u8],
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        let syndrome = Self::bin_matrix_product_paritied(
+            &self.encode_matrix[..self.m as usize],
+            byteorder::BigEndian::read_u128(buffer),
+        ) as u16;
+        let size = (self.encodable_size + self.m + 1) as usize;
+        let syndromes = &self.syndromes[..size];
+        (0..size).flat_map(move |i| {
+            (i + 1..size)
+                .filter(move |&j| syndromes[i] ^ syndromes[j] == syndrome)
+                .map(move |j| (i, j))
+        })
+    }
+
     #[cfg(feature = "no-panics")]
     #[inline]
     fn encode_assertions(&self, _encodable: u128) {}
@@ -82,7 +135,7 @@ impl SecDed128 {
         match encodable & self.mask as u128 {
             0 => {}
             _ => {
-                let mut buffer: [u8; 16] = unsafe { core::mem::uninitialized() };
+                let mut buffer: [u8; 16] = [0; 16];
                 byteorder::BigEndian::write_u128(&mut buffer[..], encodable);
                 panic!(
                     "{:?} overlaps with the code-correction slot, which is the right-most {} bits ",
@@ -94,7 +147,7 @@ impl SecDed128 {
         #[allow(clippy::cast_lossless)]
         match 1u128.overflowing_shl((self.encodable_size + self.m + 1) as u32) {
             (value, false) if encodable > value => {
-                let mut buffer: [u8; 16] = unsafe { core::mem::uninitialized() };
+                let mut buffer: [u8; 16] = [0; 16];
                 byteorder::BigEndian::write_u128(&mut buffer[..], encodable);
                 panic!(
                     "{:?} is too big to be encoded on {} bits",
@@ -138,30 +191,41 @@ impl SecDedCodec for SecDed128 {
     /// Decodes the data IN-PLACE
     /// # Arguments:
     /// * `data`: The slice of data to decode.  
-    /// The last `secded.code_size()` bits will be reset to 0, a single error will be corrected implicitly.
+    ///   The last `secded.code_size()` bits will be reset to 0, a single error will be corrected implicitly.
     /// # Returns:
     /// `Ok(())` if the data slice's correctness has been checked: 0 error found or 1 found and corrected.
     /// `Err(())` if 2 errors were detected.
     /// # Panics:
     /// Panics if `data.len() != 16`
     fn decode(&self, buffer: &mut [u8]) -> Result<(), ()> {
+        match self.decode_with_correction(buffer) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(()),
+        }
+    }
+
+    fn decode_with_correction(&self, buffer: &mut [u8]) -> Result<Correction, Uncorrectable> {
         let mut decodable = byteorder::BigEndian::read_u128(buffer);
         let syndrome =
             Self::bin_matrix_product_paritied(&self.encode_matrix[..self.m as usize], decodable)
                 as u16;
         if syndrome == 0 {
-            buffer[15] &= !self.mask;
-            return Ok(());
+            byteorder::BigEndian::write_u128(buffer, decodable & !u128::from(self.mask));
+            return Ok(Correction::None);
         }
         for (i, s) in self.syndromes.iter().enumerate() {
             if *s == syndrome {
                 decodable ^= 1 << i;
-                byteorder::BigEndian::write_u128(buffer, decodable);
-                buffer[15] &= !self.mask;
-                return Ok(());
+                byteorder::BigEndian::write_u128(buffer, decodable & !u128::from(self.mask));
+                return Ok(Correction::Bit {
+                    bit: i,
+                    syndrome: syndrome.into(),
+                });
             }
         }
-        Err(())
+        Err(Uncorrectable {
+            syndrome: syndrome.into(),
+        })
     }
 }
 
@@ -229,3 +293,79 @@ fn codec() {
     hamming.decode(&mut buffer).unwrap();
     assert_eq!(&test_value[..15], &buffer[..15])
 }
+
+#[test]
+fn odd_parity_payloads() {
+    let hamming = SecDed128::new(120);
+    let mut expected = [0; 16];
+    expected[12] = 7;
+    let mut buffer = expected;
+    hamming.encode(&mut buffer);
+    assert_eq!(buffer, [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 7, 0, 0, 217]);
+    assert_eq!(buffer.iter().map(|x| x.count_ones()).sum::<u32>() % 2, 0);
+    for i in 0..128 {
+        let mut local_buffer = buffer;
+        local_buffer[i / 8] ^= 1 << (i % 8);
+        assert_eq!(hamming.decode(&mut local_buffer), Ok(()));
+        assert_eq!(local_buffer, expected);
+    }
+}
+
+#[test]
+fn double_errors() {
+    let hamming = SecDed128::new(120);
+    let mut buffer = [0; 16];
+    buffer[12] = 5;
+    hamming.encode(&mut buffer);
+    for i in 0..128 {
+        for j in (i + 1)..128 {
+            let mut local_buffer = buffer;
+            local_buffer[i / 8] ^= 1 << (i % 8);
+            local_buffer[j / 8] ^= 1 << (j % 8);
+            assert_eq!(hamming.decode(&mut local_buffer), Err(()));
+        }
+    }
+}
+
+#[test]
+fn double_error_candidates() {
+    let hamming = SecDed128::new(90);
+    let mut buffer = [0; 16];
+    buffer[12] = 5;
+    hamming.encode(&mut buffer);
+    let size = hamming.encodable_size() + hamming.code_size();
+    for i in 0..size {
+        for j in (i + 1)..size {
+            let mut local_buffer = buffer;
+            local_buffer[15 - i / 8] ^= 1 << (i % 8);
+            local_buffer[15 - j / 8] ^= 1 << (j % 8);
+            assert!(hamming
+                .double_error_candidates(&local_buffer)
+                .any(|pair| pair == (i, j)));
+        }
+    }
+}
+
+#[test]
+fn byte_errors() {
+    let sbd = SecDed128::new_sbd(115);
+    assert_eq!(sbd.code_size(), 13);
+    let mut expected = [0; 16];
+    expected[1] = 0x5a;
+    expected[9] = 0xa5;
+    let mut encoded = expected;
+    sbd.encode(&mut encoded);
+    for byte in 0..16 {
+        for error in 2..=255u8 {
+            let mut buffer = encoded;
+            buffer[byte] ^= error;
+            if error.count_ones() > 1 {
+                assert!(sbd.decode(&mut buffer).is_err());
+            }
+        }
+    }
+    let mut buffer = encoded;
+    buffer[4] ^= 1 << 3;
+    assert_eq!(sbd.decode(&mut buffer), Ok(()));
+    assert_eq!(buffer, expected);
+}
@@ -8,8 +8,8 @@ use crate::*;
 pub struct SecDed64 {
     encodable_size: u8,
     m: u8,
-    mask: u8,
-    pub(crate) encode_matrix: [u64; 6],
+    mask: u16,
+    pub(crate) encode_matrix: [u64; 10],
     syndromes: [u16; 64],
 }
 
@@ -21,7 +21,7 @@ impl SecDed64 {
             result ^= (*x & value).parity();
             result <<= 1;
         }
-        result |= result.parity();
+        result |= result.parity() ^ value.parity();
         result
     }
 
@@ -30,7 +30,7 @@ impl SecDed64 {
             panic!("This implementation is based on u64, and can thus only encode payloads of at most 57 bits");
         }
         let m = hamming_size(encodable_size);
-        let mut encode_matrix = [0; 6];
+        let mut encode_matrix = [0; 10];
         for i in 1..=(2_u64.pow(m as u32)) {
             if i.count() < 2 {
                 continue;
@@ -46,6 +46,35 @@ impl SecDed64 {
                 *x |= 1 << (m - i);
             }
         }
+        Self::from_matrix(encodable_size, m, encode_matrix)
+    }
+
+    /// Builds a SEC-DED-SBD codec: on top of correcting single errors and detecting double errors like
+    /// `new`'s, it detects any error confined to one byte of the slice, and never miscorrects it.
+    /// This takes a few more code bits, see `code_size()`.
+    /// # Panics:
+    /// Panics if `encodable_size > 53`
+    pub fn new_sbd(encodable_size: usize) -> Self {
+        let (m, columns) = match sbd::sbd_columns(encodable_size, 64, 10) {
+            Some(found) => found,
+            None => panic!(
+                "Can't build a SEC-DED-SBD code for {} bits payloads in 64 bits words",
+                encodable_size
+            ),
+        };
+        let mut encode_matrix = [0; 10];
+        for (k, x) in encode_matrix.iter_mut().enumerate().take(m) {
+            for (bit, column) in columns.iter().enumerate() {
+                *x |= u64::from(column >> (m - 1 - k) & 1) << (m + 1 + bit);
+            }
+            *x |= 1 << (m - k);
+        }
+        let secded = Self::from_matrix(encodable_size, m, encode_matrix);
+        sbd::assert_byte_errors_detected(&secded.syndromes[..=(encodable_size + m)]);
+        secded
+    }
+
+    fn from_matrix(encodable_size: usize, m: usize, encode_matrix: [u64; 10]) -> Self {
         let mut syndromes = [0; 64];
         for (error_bit, syndrome) in syndromes
             .iter_mut()
@@ -63,12 +92,36 @@ impl SecDed64 {
         SecDed64 {
             encodable_size: encodable_size as u8,
             m: m as u8,
-            mask: { (0..=m).map(|x| 1u8 << x).sum::<u8>() },
+            mask: { (0..=m).map(|x| 1u16 << x).sum::<u16>() },
             encode_matrix,
             syndromes,
         }
     }
 
+    /// Lists every pair of bits `(i, j)`, `i < j`, whose combined errors would produce the syndrome of
+    /// `buffer`, counting bits from the right-most bit of the slice.
+    ///
+    /// When `decode` returns `Err(())`, one of these pairs is the double error that was detected if no more than
+    /// 2 bits flipped, which lets higher layers resolve the ambiguity with outside information.
+    /// # Panics:
+    /// Panics if `buffer.len() != 8`
+    pub fn double_error_candidates<'a>(
+        &'a self,
+        buffer: &[u8],
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        let syndrome = Self::bin_matrix_product_paritied(
+            &self.encode_matrix[..self.m as usize],
+            byteorder::BigEndian::read_u64(buffer),
+        ) as u16;
+        let size = (self.encodable_size + self.m + 1) as usize;
+        let syndromes = &self.syndromes[..size];
+        (0..size).flat_map(move |i| {
+            (i + 1..size)
+                .filter(move |&j| syndromes[i] ^ syndromes[j] == syndrome)
+                .map(move |j| (i, j))
+        })
+    }
+
     #[cfg(feature = "no-panics")]
     #[inline]
     fn encode_assertions(&self, _buffer: u64) {}
@@ -80,7 +133,7 @@ impl SecDed64 {
         match encodable & (self.mask as u64) {
             0 => {}
             _ => {
-                let mut buffer: [u8; 8] = unsafe { core::mem::uninitialized() };
+                let mut buffer: [u8; 8] = [0; 8];
                 byteorder::BigEndian::write_u64(&mut buffer[..], encodable);
                 panic!(
                     "{:?} overlaps with the code-correction slot, which is the right-most {} bits ",
@@ -92,7 +145,7 @@ impl SecDed64 {
         #[allow(clippy::cast_lossless)]
         match 1u64.overflowing_shl((self.encodable_size + self.m + 1) as u32) {
             (value, false) if encodable > value => {
-                let mut buffer: [u8; 8] = unsafe { core::mem::uninitialized() };
+                let mut buffer: [u8; 8] = [0; 8];
                 byteorder::BigEndian::write_u64(&mut buffer[..], encodable);
                 panic!(
                     "{:?} is too big to be encoded on {} bits",
@@ -136,30 +189,41 @@ impl SecDedCodec for SecDed64 {
     /// Decodes the data IN-PLACE
     /// # Arguments:
     /// * `data`: The slice of data to decode.  
-    /// The last `secded.code_size()` bits will be reset to 0, a single error will be corrected implicitly.
+    ///   The last `secded.code_size()` bits will be reset to 0, a single error will be corrected implicitly.
     /// # Returns:
     /// `Ok(())` if the data slice's correctness has been checked: 0 error found or 1 found and corrected.
     /// `Err(())` if 2 errors were detected.
     /// # Panics:
     /// Panics if `data.len() != 8`
     fn decode(&self, buffer: &mut [u8]) -> Result<(), ()> {
-        let mut decodable = byteorder::BigEndian::read_u64(&buffer);
+        match self.decode_with_correction(buffer) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(()),
+        }
+    }
+
+    fn decode_with_correction(&self, buffer: &mut [u8]) -> Result<Correction, Uncorrectable> {
+        let mut decodable = byteorder::BigEndian::read_u64(buffer);
         let syndrome =
             Self::bin_matrix_product_paritied(&self.encode_matrix[..self.m as usize], decodable)
                 as u16;
         if syndrome == 0 {
-            buffer[7] &= !self.mask;
-            return Ok(());
+            byteorder::BigEndian::write_u64(buffer, decodable & !u64::from(self.mask));
+            return Ok(Correction::None);
         }
         for (i, s) in self.syndromes.iter().enumerate() {
             if *s == syndrome {
                 decodable ^= 1 << i;
-                byteorder::BigEndian::write_u64(buffer, decodable);
-                buffer[7] &= !self.mask;
-                return Ok(());
+                byteorder::BigEndian::write_u64(buffer, decodable & !u64::from(self.mask));
+                return Ok(Correction::Bit {
+                    bit: i,
+                    syndrome: syndrome.into(),
+                });
             }
         }
-        Err(())
+        Err(Uncorrectable {
+            syndrome: syndrome.into(),
+        })
     }
 }
 
@@ -255,3 +319,94 @@ fn codec() {
     }
     assert!(!should_panic)
 }
+
+#[test]
+fn odd_parity_payloads() {
+    // The last code bit is the overall parity of the codeword since 2.0.0, which changed the codewords of
+    // odd parity payloads: this one is pinned to catch any further change of the format.
+    let secded = SecDed64::new(57);
+    let expected = [0, 0, 0, 0, 7, 0, 0, 0];
+    let mut encode_buffer = expected;
+    secded.encode(&mut encode_buffer);
+    assert_eq!(encode_buffer, [0, 0, 0, 0, 7, 0, 0, 94]);
+    assert_eq!(
+        encode_buffer.iter().map(|x| x.count_ones()).sum::<u32>() % 2,
+        0
+    );
+    for i in 0..64 {
+        let mut local_buffer = encode_buffer;
+        local_buffer[i / 8] ^= 1 << (i % 8);
+        assert_eq!(secded.decode(&mut local_buffer), Ok(()));
+        assert_eq!(local_buffer, expected);
+    }
+}
+
+#[test]
+fn double_errors() {
+    let secded = SecDed64::new(57);
+    let mut encode_buffer = [0, 0, 0, 0, 5, 0, 0, 0];
+    secded.encode(&mut encode_buffer);
+    for i in 0..64 {
+        for j in (i + 1)..64 {
+            let mut local_buffer = encode_buffer;
+            local_buffer[i / 8] ^= 1 << (i % 8);
+            local_buffer[j / 8] ^= 1 << (j % 8);
+            assert_eq!(secded.decode(&mut local_buffer), Err(()));
+        }
+    }
+}
+
+#[test]
+fn double_error_candidates() {
+    let secded = SecDed64::new(57);
+    let mut encode_buffer = [0, 0, 0, 0, 5, 0, 0, 0];
+    secded.encode(&mut encode_buffer);
+    assert_eq!(secded.double_error_candidates(&encode_buffer).count(), 0);
+    for i in 0..64 {
+        for j in (i + 1)..64 {
+            let mut local_buffer = encode_buffer;
+            local_buffer[7 - i / 8] ^= 1 << (i % 8);
+            local_buffer[7 - j / 8] ^= 1 << (j % 8);
+            let candidates: Vec<_> = secded.double_error_candidates(&local_buffer).collect();
+            assert!(candidates.contains(&(i, j)));
+            assert_eq!(candidates.len(), 32);
+        }
+    }
+}
+
+#[test]
+fn byte_errors() {
+    let (plain, sbd) = (SecDed64::new(53), SecDed64::new_sbd(53));
+    assert_eq!(sbd.code_size(), 11);
+    let expected = [0x02, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xd0, 0];
+    let (mut plain_buffer, mut sbd_buffer) = (expected, expected);
+    plain.encode(&mut plain_buffer);
+    sbd.encode(&mut sbd_buffer);
+    let mut miscorrected = 0;
+    for byte in 0..8 {
+        for error in 1..=255u8 {
+            let mut buffer = sbd_buffer;
+            buffer[byte] ^= error;
+            if error.count_ones() == 1 {
+                assert_eq!(sbd.decode(&mut buffer), Ok(()));
+                assert_eq!(buffer, expected);
+                continue;
+            }
+            assert!(sbd.decode(&mut buffer).is_err());
+            let mut buffer = plain_buffer;
+            buffer[byte] ^= error;
+            if plain.decode(&mut buffer).is_ok() {
+                miscorrected += 1;
+            }
+        }
+    }
+    assert!(miscorrected > 0);
+    for i in 0..64 {
+        for j in (i + 1)..64 {
+            let mut buffer = sbd_buffer;
+            buffer[7 - i / 8] ^= 1 << (i % 8);
+            buffer[7 - j / 8] ^= 1 << (j % 8);
+            assert!(sbd.decode(&mut buffer).is_err());
+        }
+    }
+}
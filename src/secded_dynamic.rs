@@ -32,7 +32,7 @@ impl SecDedDynamic {
             }
             result <<= 1;
         }
-        if result.parity() != 0 {
+        if result.parity() != value.parity() {
             result |= one;
         }
         result
@@ -83,7 +83,7 @@ impl SecDedDynamic {
             error <<= 1;
         }
         let mut mask = bitvec!();
-        for _ in 0..m {
+        for _ in 0..=m {
             mask <<= 1;
             mask |= 1;
         }
@@ -163,6 +163,12 @@ impl SecDedCodec for SecDedDynamic {
         copy_into(&buffer, data);
     }
     fn decode(&self, data: &mut [u8]) -> Result<(), ()> {
+        match self.decode_with_correction(data) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(()),
+        }
+    }
+    fn decode_with_correction(&self, data: &mut [u8]) -> Result<Correction, Uncorrectable> {
         let mut buffer: Bitvec = Bitvec({
             let mut inner = VecDeque::with_capacity(data.len());
             for &x in data.iter() {
@@ -173,15 +179,20 @@ impl SecDedCodec for SecDedDynamic {
         let syndrome = Self::bin_matrix_product_paritied(self.encode_matrix.as_ref(), &buffer);
         if syndrome.is_null() {
             self.mask.mask_not_buffer(data);
-            return Ok(());
+            return Ok(Correction::None);
         }
         if let Some(correction) = self.syndromes.get(&syndrome) {
             buffer ^= correction;
             copy_into(&buffer, data);
             self.mask.mask_not_buffer(data);
-            Ok(())
+            Ok(Correction::Bit {
+                bit: correction.trailing_zeros(),
+                syndrome: syndrome.to_u64_be(),
+            })
         } else {
-            Err(())
+            Err(Uncorrectable {
+                syndrome: syndrome.to_u64_be(),
+            })
         }
     }
 }
@@ -212,6 +223,58 @@ fn codec() {
     assert!(!should_panic)
 }
 
+#[test]
+fn odd_parity_payloads() {
+    let (secded, fixed) = (SecDedDynamic::new(57), SecDed64::new(57));
+    let expected = [0, 0, 0, 0, 7, 0, 0, 0];
+    let (mut encode_buffer, mut fixed_buffer) = (expected, expected);
+    secded.encode(&mut encode_buffer);
+    fixed.encode(&mut fixed_buffer);
+    assert_eq!(encode_buffer, fixed_buffer);
+    for i in 0..64 {
+        let mut local_buffer = encode_buffer;
+        local_buffer[i / 8] ^= 1 << (i % 8);
+        assert_eq!(secded.decode(&mut local_buffer), Ok(()));
+        assert_eq!(local_buffer, expected);
+    }
+}
+
+#[test]
+fn corrections() {
+    let dynamic = SecDedDynamic::new(57);
+    let fixed = SecDed64::new(57);
+    let mut encode_buffer = [0, 0, 0, 0, 5, 0, 0, 0];
+    fixed.encode(&mut encode_buffer);
+    for i in 0..64 {
+        let mut local_buffer = encode_buffer;
+        local_buffer[i / 8] ^= 1 << (i % 8);
+        let mut fixed_buffer = local_buffer;
+        assert_eq!(
+            dynamic.decode_with_correction(&mut local_buffer),
+            fixed.decode_with_correction(&mut fixed_buffer)
+        );
+    }
+}
+
+#[test]
+fn double_errors() {
+    let secded = SecDedDynamic::new(57);
+    let expected = [0, 0, 0, 0, 5, 0, 0, 0];
+    let mut encode_buffer = expected;
+    secded.encode(&mut encode_buffer);
+    let mut local_buffer = encode_buffer;
+    secded.decode(&mut local_buffer).unwrap();
+    assert_eq!(local_buffer, expected);
+    for i in 0..64 {
+        for j in (i + 1)..64 {
+            let mut local_buffer = encode_buffer;
+            local_buffer[i / 8] ^= 1 << (i % 8);
+            local_buffer[j / 8] ^= 1 << (j % 8);
+            assert_eq!(secded.decode(&mut local_buffer), Err(()));
+        }
+    }
+}
+
 #[cfg(feature = "bench")]
 #[bench]
 fn encode(b: &mut test::Bencher) {
@@ -0,0 +1,193 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::protected::{read_pod, write_pod, Codeword, Pod};
+use crate::scrub::{ScrubReport, Scrubber};
+use crate::*;
+use core::marker::PhantomData;
+
+/// A vector which stores each of its elements as a SECDED codeword, using `SecDed64` for elements of up
+/// to 7 bytes and `SecDed128` for elements of up to `MAX_PROTECTED_SIZE` bytes.
+///
+/// Reading an element decodes a copy of its codeword: use `scrub` periodically to write corrections back.
+pub struct SecDedVec<T: Pod> {
+    words: Vec<u8>,
+    word_size: usize,
+    codec: &'static dyn SecDedCodec,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Pod> SecDedVec<T> {
+    /// # Panics:
+    /// Panics if `size_of::<T>() > MAX_PROTECTED_SIZE`
+    pub fn new() -> Self {
+        let codeword = Codeword::for_size(core::mem::size_of::<T>());
+        SecDedVec {
+            words: Vec::new(),
+            word_size: codeword.bytes().len(),
+            codec: codeword.codec(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut vec = Self::new();
+        vec.words.reserve(capacity * vec.word_size);
+        vec
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len() / self.word_size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Returns the size of each element's codeword, in bytes.
+    pub fn word_size(&self) -> usize {
+        self.word_size
+    }
+
+    /// Returns the codewords backing this vector.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.words
+    }
+
+    fn word(&self, index: usize) -> &[u8] {
+        &self.words[index * self.word_size..(index + 1) * self.word_size]
+    }
+
+    fn word_mut(&mut self, index: usize) -> &mut [u8] {
+        &mut self.words[index * self.word_size..(index + 1) * self.word_size]
+    }
+
+    fn encode_into(codec: &dyn SecDedCodec, value: &T, word: &mut [u8]) {
+        for x in word.iter_mut() {
+            *x = 0;
+        }
+        write_pod(value, word);
+        codec.encode(word);
+    }
+
+    fn decode_word(&self, word: &[u8]) -> Result<T, ()> {
+        let mut buffer = [0u8; 16];
+        let buffer = &mut buffer[..self.word_size];
+        buffer.copy_from_slice(word);
+        self.codec.decode(buffer)?;
+        Ok(read_pod(buffer))
+    }
+
+    pub fn push(&mut self, value: T) {
+        let start = self.words.len();
+        self.words.resize(start + self.word_size, 0);
+        Self::encode_into(self.codec, &value, &mut self.words[start..]);
+    }
+
+    pub fn pop(&mut self) -> Option<Result<T, ()>> {
+        let len = self.len();
+        if len == 0 {
+            return None;
+        }
+        let value = self.decode_word(self.word(len - 1));
+        self.words.truncate((len - 1) * self.word_size);
+        Some(value)
+    }
+
+    /// Returns `None` if `index` is out of bounds, or the decoded element, with a single error corrected
+    /// implicitly. `Err(())` if 2 errors were detected in its codeword.
+    pub fn get(&self, index: usize) -> Option<Result<T, ()>> {
+        if index < self.len() {
+            Some(self.decode_word(self.word(index)))
+        } else {
+            None
+        }
+    }
+
+    /// # Panics:
+    /// Panics if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, value: T) {
+        let codec = self.codec;
+        Self::encode_into(codec, &value, self.word_mut(index));
+    }
+
+    /// Iterates over the decoded elements, `Err(())` marking those in which 2 errors were detected.
+    pub fn iter(&self) -> impl Iterator<Item = Result<T, ()>> + '_ {
+        self.words
+            .chunks(self.word_size)
+            .map(move |word| self.decode_word(word))
+    }
+
+    /// Corrects single errors in every codeword in place, and reports the indices of the corrected and
+    /// uncorrectable elements.
+    pub fn scrub(&mut self) -> ScrubReport {
+        Scrubber::new(self.codec).scrub(&mut self.words)
+    }
+
+    /// Decodes all elements, or returns the indices of those in which 2 errors were detected.
+    pub fn into_inner(self) -> Result<Vec<T>, Vec<usize>> {
+        let mut values = Vec::with_capacity(self.len());
+        let mut uncorrectable = Vec::new();
+        for (i, value) in self.iter().enumerate() {
+            match value {
+                Ok(value) => values.push(value),
+                Err(()) => uncorrectable.push(i),
+            }
+        }
+        if uncorrectable.is_empty() {
+            Ok(values)
+        } else {
+            Err(uncorrectable)
+        }
+    }
+}
+
+impl<T: Pod> Default for SecDedVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Pod> core::iter::FromIterator<T> for SecDedVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = SecDedVec::new();
+        for value in iter {
+            vec.push(value);
+        }
+        vec
+    }
+}
+
+impl<T: Pod> Extend<T> for SecDedVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+#[test]
+fn secded_vec() {
+    let mut vec: SecDedVec<u16> = (0..100).collect();
+    assert_eq!(vec.len(), 100);
+    assert_eq!(vec.word_size(), 8);
+    vec.set(10, 1000);
+    assert_eq!(vec.get(10), Some(Ok(1000)));
+    assert_eq!(vec.get(100), None);
+    vec.words[8 * 3 + 2] ^= 1 << 4;
+    vec.words[8 * 50 + 7] ^= 1;
+    vec.words[8 * 70] ^= 0b11;
+    assert_eq!(vec.get(3), Some(Ok(3)));
+    assert_eq!(vec.iter().filter(|x| x.is_err()).count(), 1);
+    let report = vec.scrub();
+    assert_eq!(report.corrected, vec![3, 50]);
+    assert_eq!(report.uncorrectable, vec![70]);
+    assert_eq!(vec.scrub().corrected, vec![]);
+    assert_eq!(vec.pop(), Some(Ok(99)));
+    assert_eq!(vec.into_inner(), Err(vec![70]));
+
+    let vec: SecDedVec<u64> = (0..10).map(|x| x << 40).collect();
+    assert_eq!(vec.word_size(), 16);
+    assert_eq!(vec.into_inner().unwrap()[9], 9 << 40);
+}
@@ -0,0 +1,295 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Monte Carlo simulation of codecs over noisy channels.
+//!
+//! A frame is made of `depth` codewords whose bits are interleaved on the channel: bit `t` of the
+//! transmission is bit `t / depth` of codeword `t % depth`, which spreads bursts over several codewords.
+
+use crate::fault::{flip_bit, Rng};
+use crate::frame::BlockLayout;
+use crate::*;
+use std::fmt::Write;
+
+/// A channel decides, bit after bit, whether the transmitted bit gets flipped.
+pub trait Channel {
+    fn flips(&mut self, rng: &mut Rng) -> bool;
+}
+
+/// Flips each bit independently with probability `p`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BinarySymmetric {
+    pub p: f64,
+}
+
+impl Channel for BinarySymmetric {
+    fn flips(&mut self, rng: &mut Rng) -> bool {
+        rng.next_f64() < self.p
+    }
+}
+
+/// A two-states Markov channel: bits are flipped with probability `error_good` in the good state, and
+/// `error_bad` in the bad state, which produces bursts of errors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GilbertElliott {
+    /// Probability of going from the good state to the bad one after each bit.
+    pub good_to_bad: f64,
+    /// Probability of going from the bad state to the good one after each bit.
+    pub bad_to_good: f64,
+    pub error_good: f64,
+    pub error_bad: f64,
+    bad: bool,
+}
+
+impl GilbertElliott {
+    pub fn new(good_to_bad: f64, bad_to_good: f64, error_good: f64, error_bad: f64) -> Self {
+        GilbertElliott {
+            good_to_bad,
+            bad_to_good,
+            error_good,
+            error_bad,
+            bad: false,
+        }
+    }
+
+    /// A channel with an average bit error rate of `rate`, whose bursts last `mean_burst_length` bits on
+    /// average, and during which bits are flipped with probability 0.5.
+    /// # Panics:
+    /// Panics unless `0 <= rate < 0.5` and `mean_burst_length >= 1`.
+    pub fn bursty(rate: f64, mean_burst_length: f64) -> Self {
+        if !(0.0..0.5).contains(&rate) || mean_burst_length < 1.0 {
+            panic!(
+                "Can't build a bursty channel with a rate of {} and bursts of {} bits",
+                rate, mean_burst_length
+            );
+        }
+        let bad_to_good = 1.0 / mean_burst_length;
+        let bad_fraction = 2.0 * rate;
+        GilbertElliott::new(
+            bad_fraction * bad_to_good / (1.0 - bad_fraction),
+            bad_to_good,
+            0.0,
+            0.5,
+        )
+    }
+
+    /// The stationary bit error rate of this channel.
+    pub fn average_error_rate(&self) -> f64 {
+        let bad_fraction = self.good_to_bad / (self.good_to_bad + self.bad_to_good);
+        bad_fraction * self.error_bad + (1.0 - bad_fraction) * self.error_good
+    }
+}
+
+impl Channel for GilbertElliott {
+    fn flips(&mut self, rng: &mut Rng) -> bool {
+        let flips = rng.next_f64()
+            < if self.bad {
+                self.error_bad
+            } else {
+                self.error_good
+            };
+        let transition = if self.bad {
+            self.bad_to_good
+        } else {
+            self.good_to_bad
+        };
+        if rng.next_f64() < transition {
+            self.bad = !self.bad;
+        }
+        flips
+    }
+}
+
+/// The outcomes of the frames simulated at a given raw error rate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimulationPoint {
+    pub raw_error_rate: f64,
+    pub frames: usize,
+    /// Frames that went through the channel untouched.
+    pub clean: usize,
+    /// Frames that were hit by errors, all of which were corrected.
+    pub corrected: usize,
+    /// Frames in which at least one codeword was reported as uncorrectable.
+    pub detected: usize,
+    /// Frames that decoded without error, but with wrong data.
+    pub miscorrected: usize,
+}
+
+impl SimulationPoint {
+    pub fn corrected_rate(&self) -> f64 {
+        self.corrected as f64 / self.frames as f64
+    }
+
+    pub fn detected_rate(&self) -> f64 {
+        self.detected as f64 / self.frames as f64
+    }
+
+    pub fn miscorrected_rate(&self) -> f64 {
+        self.miscorrected as f64 / self.frames as f64
+    }
+}
+
+pub const CSV_HEADER: &str = "raw_error_rate,frames,clean,corrected,detected,miscorrected,\
+corrected_rate,detected_rate,miscorrected_rate";
+
+/// Formats `points` as CSV, starting with `CSV_HEADER`.
+pub fn to_csv(points: &[SimulationPoint]) -> String {
+    let mut csv = String::from(CSV_HEADER);
+    csv.push('\n');
+    for point in points {
+        writeln!(
+            csv,
+            "{:e},{},{},{},{},{},{:e},{:e},{:e}",
+            point.raw_error_rate,
+            point.frames,
+            point.clean,
+            point.corrected,
+            point.detected,
+            point.miscorrected,
+            point.corrected_rate(),
+            point.detected_rate(),
+            point.miscorrected_rate()
+        )
+        .unwrap();
+    }
+    csv
+}
+
+/// Runs random payloads encoded by a codec through a `Channel`, and classifies the decoded frames.
+pub struct Simulation<C> {
+    codec: C,
+    frames: usize,
+    depth: usize,
+    seed: u64,
+}
+
+impl<C: SecDedCodec> Simulation<C> {
+    pub fn new(codec: C, frames: usize) -> Self {
+        Simulation {
+            codec,
+            frames,
+            depth: 1,
+            seed: 0,
+        }
+    }
+
+    /// Interleaves `depth` codewords per frame.
+    pub fn interleaved(mut self, depth: usize) -> Self {
+        self.depth = depth.max(1);
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Simulates `self.frames` frames through `channel`, labelling the results with `raw_error_rate`.
+    pub fn run<Ch: Channel>(&self, raw_error_rate: f64, mut channel: Ch) -> SimulationPoint {
+        let mut rng = Rng::new(self.seed);
+        let word_size = BlockLayout::of(&self.codec).word_size;
+        let (code_size, width) = (
+            self.codec.code_size(),
+            self.codec.encodable_size() + self.codec.code_size(),
+        );
+        let mut payloads = vec![vec![0u8; word_size]; self.depth];
+        let mut words = payloads.clone();
+        let mut point = SimulationPoint {
+            raw_error_rate,
+            frames: self.frames,
+            clean: 0,
+            corrected: 0,
+            detected: 0,
+            miscorrected: 0,
+        };
+        for _ in 0..self.frames {
+            for (payload, word) in payloads.iter_mut().zip(words.iter_mut()) {
+                for x in payload.iter_mut() {
+                    *x = 0;
+                }
+                for bit in code_size..width {
+                    if rng.next_u64() & 1 == 1 {
+                        flip_bit(payload, bit);
+                    }
+                }
+                word.copy_from_slice(payload);
+                self.codec.encode(word);
+            }
+            let mut errors = 0;
+            for t in 0..width * self.depth {
+                if channel.flips(&mut rng) {
+                    flip_bit(&mut words[t % self.depth], t / self.depth);
+                    errors += 1;
+                }
+            }
+            let (mut detected, mut wrong) = (false, false);
+            for (payload, word) in payloads.iter().zip(words.iter_mut()) {
+                match self.codec.decode(word) {
+                    Ok(()) => wrong |= payload != word,
+                    Err(()) => detected = true,
+                }
+            }
+            if detected {
+                point.detected += 1;
+            } else if wrong {
+                point.miscorrected += 1;
+            } else if errors > 0 {
+                point.corrected += 1;
+            } else {
+                point.clean += 1;
+            }
+        }
+        point
+    }
+
+    /// Runs the simulation for each of `rates`, building the channel for each rate with `channel`.
+    pub fn sweep<Ch: Channel, F: FnMut(f64) -> Ch>(
+        &self,
+        rates: &[f64],
+        mut channel: F,
+    ) -> Vec<SimulationPoint> {
+        rates
+            .iter()
+            .map(|&rate| self.run(rate, channel(rate)))
+            .collect()
+    }
+}
+
+/// Returns `points` rates spaced logarithmically between `from` and `to`, both included.
+pub fn log_space(from: f64, to: f64, points: usize) -> Vec<f64> {
+    if points < 2 {
+        return vec![from];
+    }
+    let ratio = (to / from).powf(1.0 / (points - 1) as f64);
+    (0..points).map(|i| from * ratio.powi(i as i32)).collect()
+}
+
+#[test]
+fn binary_symmetric() {
+    let simulation = Simulation::new(SecDed64::new(57), 2000).seed(1);
+    let points = simulation.sweep(&[0.0, 1e-3, 0.2], |p| BinarySymmetric { p });
+    assert_eq!(points[0].clean, 2000);
+    assert_eq!(points[1].miscorrected, 0);
+    assert!(points[1].corrected > 0);
+    assert!(points[2].detected > 0 && points[2].miscorrected > 0);
+    let csv = to_csv(&points);
+    assert_eq!(csv.lines().count(), 4);
+    assert!(csv.starts_with(CSV_HEADER));
+}
+
+#[test]
+fn interleaving_bursts() {
+    let channel = GilbertElliott::bursty(1e-3, 4.0);
+    assert!((channel.average_error_rate() - 1e-3).abs() < 1e-9);
+    let plain = Simulation::new(SecDed64::new(26), 8 * 3000)
+        .seed(2)
+        .run(1e-3, channel);
+    let interleaved = Simulation::new(SecDed64::new(26), 3000)
+        .interleaved(8)
+        .seed(2)
+        .run(1e-3, channel);
+    assert!(
+        plain.detected + plain.miscorrected > 2 * (interleaved.detected + interleaved.miscorrected)
+    );
+}
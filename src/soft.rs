@@ -0,0 +1,298 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Soft-input decoding, for receivers which know how reliable each hard bit is.
+//!
+//! Reliabilities are given per bit, counted from the right-most bit of the slice, as the magnitude of the
+//! bit's log-likelihood ratio: the higher, the more likely the received bit is right. The likelihood of a
+//! codeword then only depends on its *analog distance* to the received bits: the sum of the reliabilities of
+//! the bits in which they differ. Each decoder returns the closest codeword it finds.
+
+use crate::fault::flip_bit;
+use crate::*;
+
+/// The outcome of a successful soft decoding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoftDecision {
+    /// The bits that were flipped back, in increasing order.
+    pub flipped: Vec<usize>,
+    /// The analog distance between the received bits and the chosen codeword.
+    pub distance: f64,
+    /// How much further the runner-up codeword was, or `f64::INFINITY` if no other was found: a margin
+    /// close to 0 means the decision was almost a coin toss.
+    pub margin: f64,
+}
+
+enum Strategy {
+    /// Chase-II: hard decoding of every flip pattern of the `n` least reliable bits.
+    Chase(usize),
+    /// Exhaustive search over the payloads, using the data columns of the `H` matrix.
+    MaximumLikelihood(Vec<u64>),
+}
+
+/// A soft-input decoder wrapping any of this crate's codecs.
+pub struct SoftDecoder<C> {
+    codec: C,
+    strategy: Strategy,
+}
+
+/// The largest `encodable_size()` accepted by `SoftDecoder::maximum_likelihood`.
+pub const MAX_ML_ENCODABLE_SIZE: usize = 24;
+
+impl<C: SecDedCodec> SoftDecoder<C> {
+    /// A Chase decoder which tries every flip pattern of the 4 least reliable bits.
+    pub fn new(codec: C) -> Self {
+        SoftDecoder {
+            codec,
+            strategy: Strategy::Chase(4),
+        }
+    }
+
+    /// Makes this decoder a Chase decoder which tries every flip pattern of the `bits` least reliable bits,
+    /// which costs `2^bits` hard decodings per codeword.
+    /// # Panics:
+    /// Panics if `bits > 16`
+    pub fn chase(mut self, bits: usize) -> Self {
+        if bits > 16 {
+            panic!("Chase decoding over {} bits would be too slow", bits);
+        }
+        self.strategy = Strategy::Chase(bits);
+        self
+    }
+
+    /// A decoder which always finds the most likely codeword, by going through all of them.
+    /// # Panics:
+    /// Panics if `codec.encodable_size() > MAX_ML_ENCODABLE_SIZE` or `codec.code_size() > 64`
+    pub fn maximum_likelihood(codec: C) -> Self {
+        if codec.encodable_size() > MAX_ML_ENCODABLE_SIZE || codec.code_size() > 64 {
+            panic!(
+                "Maximum likelihood decoding of {} bits payloads would be too slow",
+                codec.encodable_size()
+            );
+        }
+        let columns = data_columns(&codec);
+        SoftDecoder {
+            codec,
+            strategy: Strategy::MaximumLikelihood(columns),
+        }
+    }
+
+    pub fn codec(&self) -> &C {
+        &self.codec
+    }
+
+    pub fn into_inner(self) -> C {
+        self.codec
+    }
+
+    /// Decodes the data IN-PLACE, using the reliability of each bit.
+    /// # Arguments:
+    /// * `data`: The slice of hard bits to decode.
+    ///   The last `code_size()` bits will be reset to 0 if decoding succeeds.
+    /// * `reliabilities`: The reliability of each of the `encodable_size() + code_size()` right-most bits
+    ///   of `data`, starting from the right-most one.
+    /// # Returns:
+    /// `Ok(decision)` describing the chosen codeword.
+    /// `Err(())` if a Chase decoder found no codeword, which maximum likelihood decoding never does.
+    /// # Panics:
+    /// Panics if `reliabilities` doesn't have a value per bit of the codeword, and whenever `decode` would.
+    #[allow(clippy::result_unit_err)]
+    pub fn decode(&self, data: &mut [u8], reliabilities: &[f64]) -> Result<SoftDecision, ()> {
+        let total_size = self.codec.encodable_size() + self.codec.code_size();
+        if reliabilities.len() != total_size {
+            panic!(
+                "Expected {} reliabilities, got {}",
+                total_size,
+                reliabilities.len()
+            );
+        }
+        let decision = match &self.strategy {
+            Strategy::Chase(bits) => self.chase_decision(data, reliabilities, *bits),
+            Strategy::MaximumLikelihood(columns) => {
+                Some(self.ml_decision(data, reliabilities, columns))
+            }
+        }
+        .ok_or(())?;
+        for &bit in decision.flipped.iter() {
+            flip_bit(data, bit);
+        }
+        self.codec.decode(data)?;
+        Ok(decision)
+    }
+
+    fn chase_decision(
+        &self,
+        data: &[u8],
+        reliabilities: &[f64],
+        bits: usize,
+    ) -> Option<SoftDecision> {
+        let mut unreliable: Vec<usize> = (0..reliabilities.len()).collect();
+        unreliable.sort_by(|&a, &b| reliabilities[a].total_cmp(&reliabilities[b]));
+        unreliable.truncate(bits);
+        let mut candidates: Vec<(Vec<usize>, f64)> = Vec::new();
+        let mut buffer = data.to_vec();
+        for pattern in 0..(1usize << unreliable.len()) {
+            buffer.copy_from_slice(data);
+            let mut flipped: Vec<usize> = unreliable
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| (pattern >> i) & 1 == 1)
+                .map(|(_, &bit)| bit)
+                .collect();
+            for &bit in flipped.iter() {
+                flip_bit(&mut buffer, bit);
+            }
+            match self.codec.decode_with_correction(&mut buffer) {
+                Ok(correction) => {
+                    for bit in correction.bits() {
+                        match flipped.iter().position(|&x| x == bit) {
+                            Some(i) => {
+                                flipped.remove(i);
+                            }
+                            None => flipped.push(bit),
+                        }
+                    }
+                }
+                Err(_) => continue,
+            }
+            flipped.sort_unstable();
+            if candidates.iter().all(|(other, _)| *other != flipped) {
+                let distance = flipped.iter().map(|&bit| reliabilities[bit]).sum();
+                candidates.push((flipped, distance));
+            }
+        }
+        candidates.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let mut candidates = candidates.into_iter();
+        let (flipped, distance) = candidates.next()?;
+        let margin = candidates
+            .next()
+            .map_or(f64::INFINITY, |(_, d)| d - distance);
+        Some(SoftDecision {
+            flipped,
+            distance,
+            margin,
+        })
+    }
+
+    fn ml_decision(&self, data: &[u8], reliabilities: &[f64], columns: &[u64]) -> SoftDecision {
+        let code_size = self.codec.code_size();
+        let code_distance = |code: u64| -> f64 {
+            (0..code_size)
+                .filter(|&bit| (code >> bit) & 1 == 1)
+                .map(|bit| reliabilities[bit])
+                .sum()
+        };
+        let received_code = (0..code_size).fold(0u64, |code, bit| {
+            code | u64::from(nth_bit_from_right(data, bit)) << bit
+        });
+        let syndrome = columns
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| nth_bit_from_right(data, code_size + i) == 1)
+            .fold(received_code, |syndrome, (_, column)| syndrome ^ column);
+        // Goes through the payload error patterns in Gray code order, so that each step flips one data bit.
+        let (mut pattern, mut data_distance, mut code_error) = (0u64, 0.0, syndrome);
+        let mut best = (0u64, code_error, code_distance(code_error));
+        let mut runner_up = f64::INFINITY;
+        for step in 1..(1u64 << columns.len()) {
+            let i = step.trailing_zeros() as usize;
+            pattern ^= 1 << i;
+            code_error ^= columns[i];
+            if (pattern >> i) & 1 == 1 {
+                data_distance += reliabilities[code_size + i];
+            } else {
+                data_distance -= reliabilities[code_size + i];
+            }
+            let distance = data_distance + code_distance(code_error);
+            if distance < best.2 {
+                runner_up = best.2;
+                best = (pattern, code_error, distance);
+            } else if distance < runner_up {
+                runner_up = distance;
+            }
+        }
+        let (pattern, code_error, distance) = best;
+        let mut flipped: Vec<usize> = (0..code_size)
+            .filter(|&bit| (code_error >> bit) & 1 == 1)
+            .collect();
+        flipped.extend(
+            (0..columns.len())
+                .filter(|&i| (pattern >> i) & 1 == 1)
+                .map(|i| code_size + i),
+        );
+        SoftDecision {
+            flipped,
+            distance,
+            margin: runner_up - distance,
+        }
+    }
+}
+
+#[test]
+fn double_errors() {
+    let decoder = SoftDecoder::new(SecDed64::new(57));
+    let expected = [1, 2, 3, 4, 5, 6, 7, 0];
+    let mut encoded = expected;
+    decoder.codec().encode(&mut encoded);
+    let mut reliabilities = vec![4.0; 64];
+    reliabilities[10] = 0.5;
+    reliabilities[40] = 0.3;
+    let mut buffer = encoded;
+    flip_bit(&mut buffer, 10);
+    flip_bit(&mut buffer, 40);
+    assert!(decoder.codec().decode(&mut buffer.clone()).is_err());
+    let decision = decoder.decode(&mut buffer, &reliabilities).unwrap();
+    assert_eq!(buffer, expected);
+    assert_eq!(decision.flipped, vec![10, 40]);
+    assert!((decision.distance - 0.8).abs() < 1e-12);
+    assert!(decision.margin > 0.0);
+    let mut buffer = encoded;
+    flip_bit(&mut buffer, 20);
+    let decision = decoder.decode(&mut buffer, &reliabilities).unwrap();
+    assert_eq!((buffer, decision.flipped), (expected, vec![20]));
+}
+
+#[test]
+fn chase_against_maximum_likelihood() {
+    use crate::fault::Rng;
+    let (chase, ml) = (
+        SoftDecoder::new(SecDed64::new(11)).chase(6),
+        SoftDecoder::maximum_likelihood(SecDed64::new(11)),
+    );
+    let mut rng = Rng::new(38);
+    let (mut hard_errors, mut chase_errors, mut ml_errors) = (0, 0, 0);
+    for _ in 0..2000 {
+        let mut expected = [0u8; 8];
+        expected[6] = rng.next_u64() as u8;
+        expected[7] = rng.next_u64() as u8 & 0xe0;
+        let mut encoded = expected;
+        ml.codec().encode(&mut encoded);
+        // BPSK over a noisy channel: each bit's reliability is the magnitude of its received LLR.
+        let mut reliabilities = Vec::new();
+        for bit in 0..16 {
+            let llr = 1.5 + (rng.next_f64() + rng.next_f64() + rng.next_f64() - 1.5) * 2.0;
+            if llr < 0.0 {
+                flip_bit(&mut encoded, bit);
+            }
+            reliabilities.push(llr.abs());
+        }
+        let mut buffer = encoded;
+        if ml.codec().decode(&mut buffer).is_err() || buffer != expected {
+            hard_errors += 1;
+        }
+        let mut buffer = encoded;
+        let ml_decision = ml.decode(&mut buffer, &reliabilities).unwrap();
+        ml_errors += (buffer != expected) as usize;
+        let mut buffer = encoded;
+        match chase.decode(&mut buffer, &reliabilities) {
+            Ok(decision) => {
+                assert!(decision.distance >= ml_decision.distance - 1e-9);
+                chase_errors += (buffer != expected) as usize;
+            }
+            Err(()) => chase_errors += 1,
+        }
+    }
+    assert!(ml_errors <= chase_errors);
+    assert!(2 * chase_errors < hard_errors);
+}
@@ -0,0 +1,245 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A single-symbol-correcting, double-symbol-detecting codec: a shortened Reed-Solomon code over `GF(16)`
+//! or `GF(256)`, with 3 check symbols.
+//!
+//! The slice is split into symbols of 4 or 8 bits from its right-most bit: symbol `i` holds the coefficient
+//! of `x^i` in the codeword's polynomial, the 3 lowest ones being the remainder of the payload by the
+//! generator polynomial `g = (x + 1)(x + alpha)(x + alpha^2)`. The minimum distance is 4 symbols, so any
+//! error confined to a symbol, such as a failed x4 or x8 memory chip, is corrected, and any error spanning
+//! 2 symbols is detected.
+
+use crate::fault::flip_bit;
+use crate::galois::GaloisField;
+use crate::*;
+
+const CHECK_SYMBOLS: usize = 3;
+
+pub struct SscDsd {
+    encodable_size: usize,
+    symbol_size: usize,
+    /// The coefficients of `x^0`, `x^1` and `x^2` in `g`, whose leading coefficient is 1.
+    generator: [u16; CHECK_SYMBOLS],
+    field: GaloisField,
+}
+
+impl SscDsd {
+    /// Builds a codec for `encodable_size` bits payloads, carried by symbols of `symbol_size` bits.
+    /// # Panics:
+    /// Panics if `symbol_size` isn't 4 or 8, if `encodable_size` isn't a non-null multiple of
+    /// `symbol_size`, or if the payload takes more than 12 symbols of 4 bits, or 252 symbols of 8 bits.
+    pub fn new(symbol_size: usize, encodable_size: usize) -> Self {
+        if symbol_size != 4 && symbol_size != 8 {
+            panic!("Symbols can only be 4 or 8 bits wide, not {}", symbol_size);
+        }
+        let field = GaloisField::new(symbol_size);
+        if encodable_size == 0
+            || encodable_size % symbol_size != 0
+            || encodable_size / symbol_size + CHECK_SYMBOLS > field.size
+        {
+            panic!(
+                "Can't encode payloads of {} bits with symbols of {} bits",
+                encodable_size, symbol_size
+            );
+        }
+        let mut generator = vec![1u16];
+        for power in 0..CHECK_SYMBOLS {
+            let root = field.alpha(power);
+            let mut next = vec![0u16; generator.len() + 1];
+            for (i, &coefficient) in generator.iter().enumerate() {
+                next[i + 1] ^= coefficient;
+                next[i] ^= field.mul(coefficient, root);
+            }
+            generator = next;
+        }
+        SscDsd {
+            encodable_size,
+            symbol_size,
+            generator: [generator[0], generator[1], generator[2]],
+            field,
+        }
+    }
+
+    pub fn symbol_size(&self) -> usize {
+        self.symbol_size
+    }
+
+    /// Returns the number of symbols in a codeword.
+    pub fn symbol_count(&self) -> usize {
+        self.encodable_size / self.symbol_size + CHECK_SYMBOLS
+    }
+
+    fn check_size(&self, data: &[u8]) {
+        let expected = self.expected_slice_size().unwrap();
+        if data.len() != expected {
+            panic!(
+                "This SscDsd codec works on {} bytes slices, not {}",
+                expected,
+                data.len()
+            );
+        }
+    }
+
+    fn symbol(&self, data: &[u8], i: usize) -> u16 {
+        (0..self.symbol_size).fold(0, |symbol, bit| {
+            symbol | u16::from(nth_bit_from_right(data, i * self.symbol_size + bit)) << bit
+        })
+    }
+
+    fn flip_symbol(&self, data: &mut [u8], i: usize, error: u16) {
+        for bit in 0..self.symbol_size {
+            if (error >> bit) & 1 == 1 {
+                flip_bit(data, i * self.symbol_size + bit);
+            }
+        }
+    }
+}
+
+impl SecDedCodec for SscDsd {
+    fn encodable_size(&self) -> usize {
+        self.encodable_size
+    }
+    fn code_size(&self) -> usize {
+        CHECK_SYMBOLS * self.symbol_size
+    }
+    fn expected_slice_size(&self) -> Option<usize> {
+        Some((self.encodable_size + self.code_size() + 7) / 8)
+    }
+
+    /// Encodes the data IN-PLACE
+    /// # Panics:
+    /// Panics if `data.len() != self.expected_slice_size().unwrap()`
+    fn encode(&self, data: &mut [u8]) {
+        self.check_size(data);
+        let mut remainder = [0u16; CHECK_SYMBOLS];
+        for i in (CHECK_SYMBOLS..self.symbol_count()).rev() {
+            let feedback = self.symbol(data, i) ^ remainder[CHECK_SYMBOLS - 1];
+            for j in (1..CHECK_SYMBOLS).rev() {
+                remainder[j] = remainder[j - 1] ^ self.field.mul(feedback, self.generator[j]);
+            }
+            remainder[0] = self.field.mul(feedback, self.generator[0]);
+        }
+        for (i, &symbol) in remainder.iter().enumerate() {
+            self.flip_symbol(data, i, symbol);
+        }
+    }
+
+    /// Decodes the data IN-PLACE, correcting any error confined to a symbol and detecting errors spanning 2.
+    /// # Panics:
+    /// Panics if `data.len() != self.expected_slice_size().unwrap()`
+    fn decode(&self, data: &mut [u8]) -> Result<(), ()> {
+        match self.decode_with_correction(data) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(()),
+        }
+    }
+
+    /// The reported syndromes pack `S0`, `S1` and `S2` in bytes 0, 1 and 2, `Sk` being the codeword's
+    /// polynomial evaluated in `alpha^k`.
+    fn decode_with_correction(&self, data: &mut [u8]) -> Result<Correction, Uncorrectable> {
+        self.check_size(data);
+        let mut syndromes = [0u16; CHECK_SYMBOLS];
+        for i in 0..self.symbol_count() {
+            let symbol = self.symbol(data, i);
+            for (k, syndrome) in syndromes.iter_mut().enumerate() {
+                *syndrome ^= self.field.mul(symbol, self.field.alpha(k * i));
+            }
+        }
+        let [s0, s1, s2] = syndromes;
+        let syndrome = u64::from(s0) | u64::from(s1) << 8 | u64::from(s2) << 16;
+        let correction = if syndrome == 0 {
+            Correction::None
+        } else if s0 != 0 && s1 != 0 && self.field.mul(s1, s1) == self.field.mul(s0, s2) {
+            // A single symbol error `e` at position `j` gives `S0 = e`, `S1 = e * alpha^j`, `S2 = e * alpha^2j`.
+            let symbol = self.field.log[self.field.div(s1, s0) as usize] as usize;
+            if symbol >= self.symbol_count() {
+                return Err(Uncorrectable { syndrome });
+            }
+            self.flip_symbol(data, symbol, s0);
+            Correction::Symbol {
+                symbol,
+                size: self.symbol_size,
+                error: s0 as u8,
+                syndrome,
+            }
+        } else {
+            return Err(Uncorrectable { syndrome });
+        };
+        for bit in 0..self.code_size() {
+            if nth_bit_from_right(data, bit) == 1 {
+                flip_bit(data, bit);
+            }
+        }
+        Ok(correction)
+    }
+}
+
+#[test]
+fn symbol_errors() {
+    let x4 = SscDsd::new(4, 48);
+    assert_eq!(x4.expected_slice_size(), Some(8));
+    let expected = [0x02, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xd0, 0];
+    let mut encoded = expected;
+    x4.encode(&mut encoded);
+    // Every error confined to a symbol is corrected.
+    for symbol in 0..x4.symbol_count() {
+        for error in 1..16u16 {
+            let mut buffer = encoded;
+            x4.flip_symbol(&mut buffer, symbol, error);
+            match x4.decode_with_correction(&mut buffer) {
+                Ok(correction @ Correction::Symbol { .. }) => {
+                    let bits: Vec<usize> = (0..4)
+                        .filter(|i| (error >> i) & 1 == 1)
+                        .map(|i| symbol * 4 + i)
+                        .collect();
+                    assert_eq!(correction.bits(), bits);
+                }
+                other => panic!("{:?} for {:x} on symbol {}", other, error, symbol),
+            }
+            assert_eq!(buffer, expected);
+        }
+    }
+    // Every error spanning 2 symbols is detected.
+    for a in 0..x4.symbol_count() {
+        for b in (a + 1)..x4.symbol_count() {
+            for (error_a, error_b) in (1..16u16).flat_map(|x| (1..16u16).map(move |y| (x, y))) {
+                let mut buffer = encoded;
+                x4.flip_symbol(&mut buffer, a, error_a);
+                x4.flip_symbol(&mut buffer, b, error_b);
+                assert!(x4.decode(&mut buffer).is_err());
+            }
+        }
+    }
+}
+
+#[test]
+fn chip_failures() {
+    use crate::fault::{FaultInjector, FaultModel};
+    let (x8, secded) = (SscDsd::new(8, 64), SecDed128::new(64));
+    let mut injector = FaultInjector::new(41);
+    let expected: Vec<u8> = (1..=11).map(|x| if x > 8 { 0 } else { x * 17 }).collect();
+    let mut encoded = expected.clone();
+    x8.encode(&mut encoded);
+    let mut secded_encoded = [0u8; 16];
+    secded_encoded[7..15].copy_from_slice(&expected[..8]);
+    secded.encode(&mut secded_encoded);
+    let mut secded_failures = 0;
+    for _ in 0..5000 {
+        let mut buffer = encoded.clone();
+        let injection = injector.inject_codeword(&x8, &mut buffer, &FaultModel::Symbol(8));
+        assert_eq!(x8.decode(&mut buffer), Ok(()), "{:?}", injection);
+        assert_eq!(buffer, expected);
+        let mut buffer = encoded.clone();
+        let first = injector.inject_codeword(&x8, &mut buffer, &FaultModel::Symbol(8));
+        let second = injector.inject_codeword(&x8, &mut buffer, &FaultModel::Symbol(8));
+        if first.flipped[0] / 8 != second.flipped[0] / 8 {
+            assert!(x8.decode(&mut buffer).is_err(), "{:?} {:?}", first, second);
+        }
+        let mut buffer = secded_encoded;
+        injector.inject_codeword(&secded, &mut buffer, &FaultModel::Symbol(8));
+        secded_failures += secded.decode(&mut buffer).is_err() as usize;
+    }
+    assert!(secded_failures > 0);
+}
@@ -0,0 +1,206 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// An error found by a codec wrapped in `Statistics`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorEvent {
+    /// A single error was corrected at `bit`, counted from the right-most bit of the slice.
+    Corrected { bit: usize, syndrome: u64 },
+    /// At least 2 errors were detected.
+    Uncorrectable { syndrome: u64 },
+}
+
+/// Receives every `ErrorEvent` found by `Statistics`.
+///
+/// Since decoding only takes `&self`, sinks may be called concurrently from several threads if the
+/// wrapped codec is shared.
+pub trait ErrorSink {
+    fn record(&self, event: ErrorEvent);
+}
+
+impl ErrorSink for () {
+    fn record(&self, _event: ErrorEvent) {}
+}
+
+impl<F: Fn(ErrorEvent)> ErrorSink for F {
+    fn record(&self, event: ErrorEvent) {
+        self(event)
+    }
+}
+
+/// A copy of the counters of `Statistics` at a given time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatisticsSnapshot {
+    pub words_decoded: usize,
+    pub uncorrectable: usize,
+    /// Number of corrections per bit position, counted from the right-most bit of the slice.
+    pub corrections: Vec<usize>,
+}
+
+impl StatisticsSnapshot {
+    pub fn total_corrections(&self) -> usize {
+        self.corrections.iter().sum()
+    }
+}
+
+/// Wraps any codec to count decoded words, corrections per bit position and uncorrectable words, and to
+/// forward each error to an `ErrorSink`.
+///
+/// All counters are atomic, so a `Statistics` can be shared between threads like the codec it wraps.
+pub struct Statistics<C, S = ()> {
+    codec: C,
+    sink: S,
+    words_decoded: AtomicUsize,
+    uncorrectable: AtomicUsize,
+    corrections: Vec<AtomicUsize>,
+}
+
+impl<C: SecDedCodec> Statistics<C> {
+    pub fn new(codec: C) -> Self {
+        Statistics::with_sink(codec, ())
+    }
+}
+
+impl<C: SecDedCodec, S: ErrorSink> Statistics<C, S> {
+    pub fn with_sink(codec: C, sink: S) -> Self {
+        let bits = codec.encodable_size() + codec.code_size();
+        Statistics {
+            codec,
+            sink,
+            words_decoded: AtomicUsize::new(0),
+            uncorrectable: AtomicUsize::new(0),
+            corrections: (0..bits).map(|_| AtomicUsize::new(0)).collect(),
+        }
+    }
+
+    pub fn codec(&self) -> &C {
+        &self.codec
+    }
+
+    pub fn into_inner(self) -> C {
+        self.codec
+    }
+
+    pub fn words_decoded(&self) -> usize {
+        self.words_decoded.load(Ordering::Relaxed)
+    }
+
+    pub fn uncorrectable(&self) -> usize {
+        self.uncorrectable.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of corrections of the `bit`th bit, counted from the right-most bit of the slice.
+    pub fn corrections(&self, bit: usize) -> usize {
+        self.corrections[bit].load(Ordering::Relaxed)
+    }
+
+    pub fn total_corrections(&self) -> usize {
+        self.corrections
+            .iter()
+            .map(|x| x.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    pub fn snapshot(&self) -> StatisticsSnapshot {
+        StatisticsSnapshot {
+            words_decoded: self.words_decoded(),
+            uncorrectable: self.uncorrectable(),
+            corrections: self
+                .corrections
+                .iter()
+                .map(|x| x.load(Ordering::Relaxed))
+                .collect(),
+        }
+    }
+
+    pub fn reset(&self) {
+        self.words_decoded.store(0, Ordering::Relaxed);
+        self.uncorrectable.store(0, Ordering::Relaxed);
+        for x in self.corrections.iter() {
+            x.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+impl<C: SecDedCodec, S: ErrorSink> SecDedCodec for Statistics<C, S> {
+    fn encodable_size(&self) -> usize {
+        self.codec.encodable_size()
+    }
+    fn code_size(&self) -> usize {
+        self.codec.code_size()
+    }
+    fn expected_slice_size(&self) -> Option<usize> {
+        self.codec.expected_slice_size()
+    }
+    fn fingerprint(&self) -> u64 {
+        self.codec.fingerprint()
+    }
+    fn encode(&self, data: &mut [u8]) {
+        self.codec.encode(data)
+    }
+    fn decode(&self, data: &mut [u8]) -> Result<(), ()> {
+        match self.decode_with_correction(data) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(()),
+        }
+    }
+    fn decode_with_correction(&self, data: &mut [u8]) -> Result<Correction, Uncorrectable> {
+        let result = self.codec.decode_with_correction(data);
+        self.words_decoded.fetch_add(1, Ordering::Relaxed);
+        match result {
+            Ok(Correction::None) => {}
+            Ok(correction @ Correction::Bit { syndrome, .. })
+            | Ok(correction @ Correction::Double { syndrome, .. })
+            | Ok(correction @ Correction::Symbol { syndrome, .. }) => {
+                for bit in correction.bits() {
+                    if let Some(counter) = self.corrections.get(bit) {
+                        counter.fetch_add(1, Ordering::Relaxed);
+                    }
+                    self.sink.record(ErrorEvent::Corrected { bit, syndrome });
+                }
+            }
+            Err(Uncorrectable { syndrome }) => {
+                self.uncorrectable.fetch_add(1, Ordering::Relaxed);
+                self.sink.record(ErrorEvent::Uncorrectable { syndrome });
+            }
+        }
+        result
+    }
+}
+
+#[test]
+fn statistics() {
+    use std::sync::Mutex;
+    let events = Mutex::new(Vec::new());
+    let secded = Statistics::with_sink(SecDed64::new(57), |event| {
+        events.lock().unwrap().push(event)
+    });
+    let expected = [0, 0, 0, 0, 5, 0, 0, 0];
+    let mut encode_buffer = expected;
+    secded.encode(&mut encode_buffer);
+    for i in 0..64 {
+        let mut local_buffer = encode_buffer;
+        local_buffer[7 - i / 8] ^= 1 << (i % 8);
+        secded.decode(&mut local_buffer).unwrap();
+        assert_eq!(local_buffer, expected);
+    }
+    let mut local_buffer = encode_buffer;
+    local_buffer[0] ^= 0b11;
+    assert!(secded.decode(&mut local_buffer).is_err());
+    let snapshot = secded.snapshot();
+    assert_eq!(snapshot.words_decoded, 65);
+    assert_eq!(snapshot.uncorrectable, 1);
+    assert_eq!(snapshot.corrections, vec![1; 64]);
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 65);
+    for (i, event) in events[..64].iter().enumerate() {
+        match event {
+            ErrorEvent::Corrected { bit, .. } => assert_eq!(*bit, i),
+            _ => panic!("{:?} should be a correction", event),
+        }
+    }
+}
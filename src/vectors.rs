@@ -0,0 +1,518 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Reference vectors computed by a codec, against which other implementations of it can be checked.
+
+use crate::fault::{flip_bit, Rng};
+use crate::frame::BlockLayout;
+use crate::*;
+use std::fmt::Write;
+
+pub(crate) struct TestVector {
+    /// The slice holding the payload, with its code bits set to 0.
+    pub payload: Vec<u8>,
+    pub codeword: Vec<u8>,
+    pub received: Vec<u8>,
+    /// The slice once decoded, or `received` if its errors are uncorrectable.
+    pub decoded: Vec<u8>,
+    /// 0 if `received` was clean, 1 if a bit was corrected, 2 if its errors are uncorrectable. The generated
+    /// decoders only correct single errors, so corrections of several bits are recorded as uncorrectable.
+    pub status: u8,
+}
+
+/// Returns `count` vectors for each error weight from 0 to 2, computed by `codec` from random payloads
+/// drawn from `seed`.
+pub(crate) fn test_vectors<C: SecDedCodec + ?Sized>(
+    codec: &C,
+    count: usize,
+    seed: u64,
+) -> Vec<TestVector> {
+    let mut rng = Rng::new(seed);
+    let word_size = BlockLayout::of(codec).word_size;
+    let (m, n) = (
+        codec.code_size(),
+        codec.encodable_size() + codec.code_size(),
+    );
+    let mut vectors = Vec::with_capacity(3 * count);
+    for _ in 0..count {
+        let mut payload = vec![0u8; word_size];
+        for bit in m..n {
+            if rng.next_u64() & 1 == 1 {
+                flip_bit(&mut payload, bit);
+            }
+        }
+        let mut codeword = payload.clone();
+        codec.encode(&mut codeword);
+        for errors in 0..=2 {
+            let mut received = codeword.clone();
+            let mut flipped = Vec::new();
+            while flipped.len() < errors {
+                let bit = rng.below(n);
+                if !flipped.contains(&bit) {
+                    flip_bit(&mut received, bit);
+                    flipped.push(bit);
+                }
+            }
+            let mut decoded = received.clone();
+            let status = match codec.decode_with_correction(&mut decoded) {
+                Ok(Correction::None) => 0,
+                Ok(correction) if correction.bits().len() == 1 => 1,
+                _ => {
+                    decoded.copy_from_slice(&received);
+                    2
+                }
+            };
+            vectors.push(TestVector {
+                payload: payload.clone(),
+                codeword: codeword.clone(),
+                received,
+                decoded,
+                status,
+            });
+        }
+    }
+    vectors
+}
+
+/// How a codec decoded a received slice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Clean,
+    /// The errors on these bits, counted from the right-most bit of the slice and in increasing order, were
+    /// corrected. When checking an implementation's outputs, an empty list matches any correction.
+    Corrected(Vec<usize>),
+    Uncorrectable,
+}
+
+impl Outcome {
+    fn name(&self) -> &'static str {
+        match self {
+            Outcome::Clean => "clean",
+            Outcome::Corrected(_) => "corrected",
+            Outcome::Uncorrectable => "uncorrectable",
+        }
+    }
+
+    fn bits(&self) -> &[usize] {
+        match self {
+            Outcome::Corrected(bits) => bits,
+            _ => &[],
+        }
+    }
+
+    fn matches(&self, expected: &Outcome) -> bool {
+        match (self, expected) {
+            (Outcome::Corrected(bits), Outcome::Corrected(_)) if bits.is_empty() => true,
+            _ => self == expected,
+        }
+    }
+}
+
+/// A codeword, received with errors on some of its bits, and what decoding it must give.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoldenVector {
+    /// The bits flipped in `codeword` to give `received`, in increasing order.
+    pub errors: Vec<usize>,
+    /// The slice holding the payload, with its code bits set to 0.
+    pub payload: Vec<u8>,
+    pub codeword: Vec<u8>,
+    pub received: Vec<u8>,
+    /// The slice once decoded, or `received` if its errors are uncorrectable.
+    pub decoded: Vec<u8>,
+    pub outcome: Outcome,
+}
+
+/// What an implementation gave for a `GoldenVector`: the encoding of its `payload`, and the decoding of its
+/// `received` slice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Output {
+    pub codeword: Vec<u8>,
+    pub decoded: Vec<u8>,
+    pub outcome: Outcome,
+}
+
+impl Output {
+    /// Parses an implementation's outputs, one line per vector and in the same order, each line being
+    /// `codeword,decoded,status[,corrected]`: slices are in hexadecimal, `status` is `clean`, `corrected`
+    /// or `uncorrectable`, and the optional `corrected` lists the corrected bits separated by spaces.
+    /// Empty lines, lines starting with `#`, and a header line starting with `codeword` are skipped.
+    pub fn parse_csv(csv: &str) -> Result<Vec<Output>, String> {
+        csv.lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| {
+                !line.is_empty() && !line.starts_with('#') && !line.starts_with("codeword")
+            })
+            .map(|(number, line)| {
+                Output::parse_line(line).map_err(|e| format!("line {}: {}", number, e))
+            })
+            .collect()
+    }
+
+    fn parse_line(line: &str) -> Result<Output, String> {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if fields.len() != 3 && fields.len() != 4 {
+            return Err(format!("expected 3 or 4 fields, found {}", fields.len()));
+        }
+        let outcome = match fields[2] {
+            "clean" => Outcome::Clean,
+            "corrected" => Outcome::Corrected(
+                fields
+                    .get(3)
+                    .map_or(Ok(Vec::new()), |bits| parse_bits(bits))?,
+            ),
+            "uncorrectable" => Outcome::Uncorrectable,
+            status => return Err(format!("unknown status `{}`", status)),
+        };
+        Ok(Output {
+            codeword: unhex(fields[0])?,
+            decoded: unhex(fields[1])?,
+            outcome,
+        })
+    }
+}
+
+/// A difference between a `GoldenVector` and an implementation's `Output`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    /// The index of the vector.
+    pub index: usize,
+    /// `codeword`, `decoded`, `outcome`, or `output` if the vector has no output, or the output no vector.
+    pub field: &'static str,
+    pub expected: String,
+    pub found: String,
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "vector {}: expected {} {}, found {}",
+            self.index, self.field, self.expected, self.found
+        )
+    }
+}
+
+/// A deterministic set of vectors describing a codec configuration, against which other implementations
+/// of it can be checked.
+///
+/// For each payload, it holds the clean codeword, the codeword with each of its bits flipped, and as many
+/// codewords with 2 distinct bits flipped as there are bits in the codeword. The first payload is all 0s,
+/// the second all 1s, and the others are drawn from the seed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoldenVectors {
+    encodable_size: usize,
+    code_size: usize,
+    fingerprint: u64,
+    vectors: Vec<GoldenVector>,
+}
+
+impl GoldenVectors {
+    pub fn new<C: SecDedCodec + ?Sized>(codec: &C, payloads: usize, seed: u64) -> Self {
+        let mut rng = Rng::new(seed);
+        let word_size = BlockLayout::of(codec).word_size;
+        let (m, n) = (
+            codec.code_size(),
+            codec.encodable_size() + codec.code_size(),
+        );
+        let pairs = n * (n - 1) / 2;
+        let mut vectors = Vec::with_capacity(payloads * (1 + n + n.min(pairs)));
+        for i in 0..payloads {
+            let mut payload = vec![0u8; word_size];
+            for bit in m..n {
+                if i == 1 || (i > 1 && rng.next_u64() & 1 == 1) {
+                    flip_bit(&mut payload, bit);
+                }
+            }
+            let mut codeword = payload.clone();
+            codec.encode(&mut codeword);
+            let mut errors: Vec<Vec<usize>> = vec![Vec::new()];
+            errors.extend((0..n).map(|bit| vec![bit]));
+            let mut doubles = Vec::new();
+            while doubles.len() < n.min(pairs) {
+                let (a, b) = (rng.below(n), rng.below(n));
+                let pair = vec![a.min(b), a.max(b)];
+                if a != b && !doubles.contains(&pair) {
+                    doubles.push(pair);
+                }
+            }
+            errors.extend(doubles);
+            for errors in errors {
+                let mut received = codeword.clone();
+                for &bit in errors.iter() {
+                    flip_bit(&mut received, bit);
+                }
+                let mut decoded = received.clone();
+                let outcome = match codec.decode_with_correction(&mut decoded) {
+                    Ok(Correction::None) => Outcome::Clean,
+                    Ok(correction) => Outcome::Corrected(correction.bits()),
+                    Err(_) => {
+                        decoded.copy_from_slice(&received);
+                        Outcome::Uncorrectable
+                    }
+                };
+                vectors.push(GoldenVector {
+                    errors,
+                    payload: payload.clone(),
+                    codeword: codeword.clone(),
+                    received,
+                    decoded,
+                    outcome,
+                });
+            }
+        }
+        GoldenVectors {
+            encodable_size: codec.encodable_size(),
+            code_size: codec.code_size(),
+            fingerprint: codec.fingerprint(),
+            vectors,
+        }
+    }
+
+    pub fn encodable_size(&self) -> usize {
+        self.encodable_size
+    }
+
+    pub fn code_size(&self) -> usize {
+        self.code_size
+    }
+
+    /// The fingerprint of the codec that computed the vectors.
+    pub fn fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+
+    pub fn vectors(&self) -> &[GoldenVector] {
+        &self.vectors
+    }
+
+    /// Exports the vectors as a JSON object, holding the codec's configuration and a `vectors` array.
+    /// Slices are in hexadecimal, and bits are counted from the right-most bit of the slice.
+    pub fn to_json(&self) -> String {
+        let mut s = String::new();
+        let _ = write!(
+            s,
+            "{{\n  \"encodable_size\": {},\n  \"code_size\": {},\n  \"fingerprint\": \"{:016x}\",\n  \"vectors\": [",
+            self.encodable_size, self.code_size, self.fingerprint
+        );
+        for (i, vector) in self.vectors.iter().enumerate() {
+            let list = |bits: &[usize]| {
+                bits.iter()
+                    .map(usize::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            let _ = write!(
+                s,
+                "{}\n    {{\"errors\": [{}], \"payload\": \"{}\", \"codeword\": \"{}\", \"received\": \"{}\", \"decoded\": \"{}\", \"status\": \"{}\", \"corrected\": [{}]}}",
+                if i == 0 { "" } else { "," },
+                list(&vector.errors),
+                hex(&vector.payload),
+                hex(&vector.codeword),
+                hex(&vector.received),
+                hex(&vector.decoded),
+                vector.outcome.name(),
+                list(vector.outcome.bits())
+            );
+        }
+        s.push_str("\n  ]\n}\n");
+        s
+    }
+
+    /// Exports the vectors as CSV, after a `#` comment holding the codec's configuration. Lists of bits are
+    /// separated by spaces, and the columns are those of the JSON export.
+    pub fn to_csv(&self) -> String {
+        let mut s = String::new();
+        let _ = writeln!(
+            s,
+            "# encodable_size={} code_size={} fingerprint={:016x}",
+            self.encodable_size, self.code_size, self.fingerprint
+        );
+        s.push_str("errors,payload,codeword,received,decoded,status,corrected\n");
+        for vector in self.vectors.iter() {
+            let list = |bits: &[usize]| {
+                bits.iter()
+                    .map(usize::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+            let _ = writeln!(
+                s,
+                "{},{},{},{},{},{},{}",
+                list(&vector.errors),
+                hex(&vector.payload),
+                hex(&vector.codeword),
+                hex(&vector.received),
+                hex(&vector.decoded),
+                vector.outcome.name(),
+                list(vector.outcome.bits())
+            );
+        }
+        s
+    }
+
+    /// Runs `codec` on each vector, to check it against vectors computed by another implementation.
+    pub fn outputs_of<C: SecDedCodec + ?Sized>(&self, codec: &C) -> Vec<Output> {
+        self.vectors
+            .iter()
+            .map(|vector| {
+                let mut codeword = vector.payload.clone();
+                codec.encode(&mut codeword);
+                let mut decoded = vector.received.clone();
+                let outcome = match codec.decode_with_correction(&mut decoded) {
+                    Ok(Correction::None) => Outcome::Clean,
+                    Ok(correction) => Outcome::Corrected(correction.bits()),
+                    Err(_) => Outcome::Uncorrectable,
+                };
+                Output {
+                    codeword,
+                    decoded,
+                    outcome,
+                }
+            })
+            .collect()
+    }
+
+    /// Checks an implementation's outputs, one per vector and in the same order, returning every difference.
+    pub fn check<I: IntoIterator<Item = Output>>(&self, outputs: I) -> Result<(), Vec<Mismatch>> {
+        let mut mismatches = Vec::new();
+        let mut outputs = outputs.into_iter();
+        for (index, vector) in self.vectors.iter().enumerate() {
+            let output = match outputs.next() {
+                Some(output) => output,
+                None => {
+                    mismatches.push(Mismatch {
+                        index,
+                        field: "output",
+                        expected: "an output".into(),
+                        found: "none".into(),
+                    });
+                    break;
+                }
+            };
+            if output.codeword != vector.codeword {
+                mismatches.push(Mismatch {
+                    index,
+                    field: "codeword",
+                    expected: hex(&vector.codeword),
+                    found: hex(&output.codeword),
+                });
+            }
+            if output.decoded != vector.decoded {
+                mismatches.push(Mismatch {
+                    index,
+                    field: "decoded",
+                    expected: hex(&vector.decoded),
+                    found: hex(&output.decoded),
+                });
+            }
+            if !output.outcome.matches(&vector.outcome) {
+                mismatches.push(Mismatch {
+                    index,
+                    field: "outcome",
+                    expected: format!("{:?}", vector.outcome),
+                    found: format!("{:?}", output.outcome),
+                });
+            }
+        }
+        if outputs.next().is_some() {
+            mismatches.push(Mismatch {
+                index: self.vectors.len(),
+                field: "output",
+                expected: "none".into(),
+                found: "an output".into(),
+            });
+        }
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(mismatches)
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn unhex(s: &str) -> Result<Vec<u8>, String> {
+    if s.len() % 2 != 0 || !s.is_ascii() {
+        return Err(format!("`{}` is not an hexadecimal slice", s));
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&s[i..i + 2], 16)
+                .map_err(|_| format!("`{}` is not an hexadecimal slice", s))
+        })
+        .collect()
+}
+
+fn parse_bits(s: &str) -> Result<Vec<usize>, String> {
+    s.split_whitespace()
+        .map(|bit| {
+            bit.parse()
+                .map_err(|_| format!("`{}` is not a bit index", bit))
+        })
+        .collect()
+}
+
+#[test]
+fn golden_vectors() {
+    let golden = GoldenVectors::new(&SecDed64::new(57), 3, 1);
+    assert_eq!(golden.vectors().len(), 3 * (1 + 64 + 64));
+    assert_eq!(golden, GoldenVectors::new(&SecDed64::new(57), 3, 1));
+    assert!(golden
+        .vectors()
+        .iter()
+        .all(|vector| match vector.errors.len() {
+            0 => vector.outcome == Outcome::Clean,
+            1 => vector.outcome == Outcome::Corrected(vector.errors.clone()),
+            _ => vector.outcome == Outcome::Uncorrectable && vector.decoded == vector.received,
+        }));
+    assert_eq!(golden.check(golden.outputs_of(&SecDed64::new(57))), Ok(()));
+    #[cfg(feature = "dyn")]
+    assert_eq!(
+        golden.check(golden.outputs_of(&SecDedDynamic::new(57))),
+        Ok(())
+    );
+    assert!(golden.check(golden.outputs_of(&Hamming::new(57))).is_err());
+    assert!(golden.to_json().contains(&format!(
+        "\"fingerprint\": \"{:016x}\"",
+        SecDed64::new(57).fingerprint()
+    )));
+    assert_eq!(golden.to_csv().lines().count(), 2 + golden.vectors().len());
+}
+
+#[test]
+fn external_outputs() {
+    let golden = GoldenVectors::new(&SecDed128::new(100), 2, 7);
+    let mut csv = String::from("codeword,decoded,status,corrected\n");
+    for output in golden.outputs_of(&SecDed128::new(100)) {
+        let bits = output.outcome.bits().iter().map(usize::to_string);
+        let _ = writeln!(
+            csv,
+            "{},{},{},{}",
+            hex(&output.codeword),
+            hex(&output.decoded),
+            output.outcome.name(),
+            bits.collect::<Vec<_>>().join(" ")
+        );
+    }
+    assert_eq!(golden.check(Output::parse_csv(&csv).unwrap()), Ok(()));
+    // Moving the corrected bits to comments leaves them unchecked.
+    let without_bits = csv.replace(",corrected,", ",corrected\n#");
+    let outputs = Output::parse_csv(&without_bits).unwrap();
+    let broken = outputs.iter().cloned().map(|mut output| {
+        if output.outcome != Outcome::Clean {
+            output.decoded[0] ^= 1;
+        }
+        output
+    });
+    let mismatches = golden.check(broken.take(10)).unwrap_err();
+    assert_eq!(mismatches.len(), 9 + 1);
+    assert_eq!(mismatches[0].index, 1);
+    assert_eq!(mismatches[9].field, "output");
+    assert!(Output::parse_csv("00,00,fixed").is_err());
+}